@@ -0,0 +1,129 @@
+//! Compares the indexed extraction of `collect_framework_info` against the
+//! scan-per-extractor access pattern it replaced, on synthetic xcframework zips.
+//!
+//! Run with `cargo bench --bench archive_index`.
+
+use std::fs::File;
+use std::hint::black_box;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use plist::{Dictionary, Value};
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+use xcspec::xcframework_processing::{collect_framework_info, CollectOptions};
+
+const ITERATIONS: u32 = 5;
+
+/// Writes an xcframework zip with `slices` slices holding `headers_per_slice` filler headers each.
+fn build_archive(path: &Path, slices: usize, headers_per_slice: usize) {
+    let mut zip = ZipWriter::new(File::create(path).expect("Failed to create benchmark archive"));
+    let options = SimpleFileOptions::default();
+    let root = "Bench.xcframework";
+
+    let mut libraries = Vec::new();
+    for slice in 0..slices {
+        let identifier = format!("platform{}-arm64", slice);
+        let framework = format!("{}/{}/Bench.framework", root, identifier);
+
+        let mut library = Dictionary::new();
+        library.insert("BinaryPath".into(), Value::from("Bench.framework/Bench"));
+        library.insert("LibraryIdentifier".into(), Value::from(identifier.as_str()));
+        library.insert("LibraryPath".into(), Value::from("Bench.framework"));
+        library.insert("SupportedArchitectures".into(), Value::Array(vec![Value::from("arm64")]));
+        library.insert("SupportedPlatform".into(), Value::from(format!("platform{}", slice)));
+        libraries.push(Value::Dictionary(library));
+
+        for header in 0..headers_per_slice {
+            zip.start_file(format!("{}/Headers/Header{}.h", framework, header), options).unwrap();
+            zip.write_all(b"#import <Foundation/Foundation.h>\n").unwrap();
+        }
+
+        let mut info = Dictionary::new();
+        info.insert("CFBundleShortVersionString".into(), Value::from("1.0"));
+        zip.start_file(format!("{}/Info.plist", framework), options).unwrap();
+        Value::Dictionary(info).to_writer_xml(&mut zip).unwrap();
+
+        let mut privacy = Dictionary::new();
+        privacy.insert("NSPrivacyTracking".into(), Value::Boolean(false));
+        zip.start_file(format!("{}/PrivacyInfo.xcprivacy", framework), options).unwrap();
+        Value::Dictionary(privacy).to_writer_xml(&mut zip).unwrap();
+
+        zip.start_file(format!("{}/Modules/Bench.swiftmodule/arm64.swiftinterface", framework), options).unwrap();
+        zip.write_all(b"// swift-module-flags: -module-name Bench -swift-version 5\nimport Foundation\n").unwrap();
+
+        zip.start_file(format!("{}/Bench", framework), options).unwrap();
+        zip.write_all(&[0u8; 4096]).unwrap();
+    }
+
+    let mut info = Dictionary::new();
+    info.insert("AvailableLibraries".into(), Value::Array(libraries));
+    zip.start_file(format!("{}/Info.plist", root), options).unwrap();
+    Value::Dictionary(info).to_writer_xml(&mut zip).unwrap();
+
+    zip.finish().unwrap();
+}
+
+/// Reproduces the previous access pattern: every extractor scans the whole archive.
+fn scan_per_extractor(path: &Path, slices: usize) {
+    let mut archive = ZipArchive::new(File::open(path).unwrap()).unwrap();
+
+    let find_and_read = |archive: &mut ZipArchive<File>, matches: &dyn Fn(&str) -> bool, read_all: bool| {
+        for i in 0..archive.len() {
+            let mut file = archive.by_index(i).unwrap();
+            if matches(file.name()) {
+                let mut buffer = Vec::new();
+                file.read_to_end(&mut buffer).unwrap();
+                black_box(buffer);
+                if !read_all {
+                    break;
+                }
+            }
+        }
+    };
+
+    find_and_read(&mut archive, &|name| name.ends_with("Info.plist"), false);
+    find_and_read(&mut archive, &|name| name.ends_with(".swiftinterface"), false);
+    find_and_read(&mut archive, &|name| name.ends_with(".swiftinterface"), false);
+    for slice in 0..slices {
+        let library = format!("platform{}-arm64/Bench.framework", slice);
+        let modules = format!("{}/Modules", library);
+        find_and_read(&mut archive, &|name| name.ends_with("Bench.framework/Bench"), false);
+        find_and_read(&mut archive, &|name| name.contains(&library) && name.ends_with("Info.plist"), false);
+        find_and_read(&mut archive, &|name| name.contains(&modules) && name.ends_with(".swiftinterface"), true);
+        find_and_read(&mut archive, &|name| name.contains(&library) && name.ends_with("PrivacyInfo.xcprivacy"), false);
+    }
+}
+
+fn measure<F: FnMut()>(mut run: F) -> Duration {
+    run();
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        run();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    let directory = tempfile::tempdir().expect("Failed to create temporary directory");
+
+    println!("{:>7} {:>8} {:>18} {:>14} {:>8}", "slices", "entries", "scan-per-extractor", "indexed", "speedup");
+    for (slices, headers_per_slice) in [(4, 250), (8, 500), (16, 1000)] {
+        let path = directory.path().join(format!("bench-{}-{}.zip", slices, headers_per_slice));
+        build_archive(&path, slices, headers_per_slice);
+
+        let scanned = measure(|| scan_per_extractor(&path, slices));
+        let indexed = measure(|| {
+            black_box(collect_framework_info(&path, &CollectOptions::default()).expect("Failed to collect framework info"));
+        });
+
+        println!(
+            "{:>7} {:>8} {:>15.2?} {:>14.2?} {:>7.1}x",
+            slices,
+            slices * (headers_per_slice + 4) + 1,
+            scanned,
+            indexed,
+            scanned.as_secs_f64() / indexed.as_secs_f64()
+        );
+    }
+}
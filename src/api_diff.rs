@@ -0,0 +1,674 @@
+use std::cmp::Ordering;
+use std::fmt;
+use serde::Serialize;
+use crate::framework_info::{ApiDeclaration, DeclarationKind, FrameworkInfo};
+use crate::utils::compare_versions;
+use crate::xcframework_processing::swift_api::split_top_level;
+
+/// Changes to the public API of a framework between two versions, with the
+/// version bump they call for.
+#[derive(Serialize, Debug)]
+pub struct ApiDiff {
+    pub old_version: Option<String>,
+    pub new_version: Option<String>,
+    /// Most severe change, `None` when the API did not change.
+    pub severity: Option<ChangeSeverity>,
+    pub changes: Vec<ApiChange>,
+    pub semver: SemverVerdict,
+}
+
+/// Change to a public declaration.
+#[derive(Serialize, Debug)]
+pub struct ApiChange {
+    pub kind: ApiChangeKind,
+    pub severity: ChangeSeverity,
+    pub declaration_kind: DeclarationKind,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_signature: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_signature: Option<String>,
+    pub message: String,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiChangeKind {
+    Added,
+    Removed,
+    SignatureChanged,
+    /// Protocol requirement added without a default implementation.
+    AddedRequirement,
+    /// Default implementation of a protocol requirement removed.
+    RemovedDefaultImplementation,
+    FrozenAdded,
+    FrozenRemoved,
+    AvailabilityTightened,
+    /// Declaration no longer available in some of the slices.
+    RemovedFromSlices,
+}
+
+/// Impact of a change on clients, from least to most severe.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeSeverity {
+    /// Existing clients keep working.
+    Additive,
+    /// Existing clients no longer compile, but built apps keep working.
+    SourceBreaking,
+    /// Apps built against the old version may fail to load or crash.
+    AbiBreaking,
+}
+
+/// Version component that changed, from none to major.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum VersionBump {
+    Downgrade,
+    None,
+    Patch,
+    Minor,
+    Major,
+}
+
+/// Whether the `marketing_version` bump satisfies semantic versioning.
+#[derive(Serialize, Debug)]
+pub struct SemverVerdict {
+    /// Smallest bump the changes call for.
+    pub required_bump: VersionBump,
+    /// Bump between the declared versions, `None` when either is missing.
+    pub actual_bump: Option<VersionBump>,
+    pub justified: bool,
+}
+
+impl ApiDiff {
+    /// Whether the API breaks without the version bump semantic versioning requires.
+    pub fn is_unjustified_break(&self) -> bool {
+        self.severity.is_some_and(|severity| severity >= ChangeSeverity::SourceBreaking) && !self.semver.justified
+    }
+}
+
+/// Compares the public API of two versions of a framework, collected with `api` enabled.
+pub fn diff_api(old: &FrameworkInfo, new: &FrameworkInfo) -> ApiDiff {
+    let old_api = old.api.as_deref().unwrap_or_default();
+    let new_api = new.api.as_deref().unwrap_or_default();
+    let mut changes = Vec::new();
+
+    // Unchanged declarations match exactly; the remaining ones pair up by kind and name
+    // (in order, for overloads), and the rest were added or removed.
+    let mut unmatched_new: Vec<&ApiDeclaration> = new_api.iter().collect();
+    let mut pairs = Vec::new();
+    let mut unmatched_old = Vec::new();
+    for declaration in old_api {
+        match unmatched_new.iter().position(|new| same_declaration(declaration, new) && declaration.signature == new.signature) {
+            Some(position) => pairs.push((declaration, unmatched_new.remove(position))),
+            None => unmatched_old.push(declaration),
+        }
+    }
+    let mut removed = Vec::new();
+    for declaration in unmatched_old {
+        match unmatched_new.iter().position(|new| same_declaration(declaration, new)) {
+            Some(position) => pairs.push((declaration, unmatched_new.remove(position))),
+            None => removed.push(declaration),
+        }
+    }
+
+    let new_slices: Vec<&str> = slices(new);
+    for (old_declaration, new_declaration) in pairs {
+        compare_declarations(old_declaration, new_declaration, &slices(old), &new_slices, &mut changes);
+    }
+    for declaration in removed {
+        let is_default = declaration.container == Some(DeclarationKind::Extension) && is_requirement(new_api, &declaration.name);
+        // Clients cannot use a declaration that is unavailable everywhere, nor link against it.
+        let unavailable = availability(&declaration.availability).iter().any(|availability| availability.platform == "*" && availability.unavailable);
+        let (kind, severity, message) = if is_default {
+            (ApiChangeKind::RemovedDefaultImplementation, ChangeSeverity::AbiBreaking, "default implementation of a protocol requirement removed")
+        } else if unavailable {
+            (ApiChangeKind::Removed, ChangeSeverity::Additive, "unavailable declaration removed")
+        } else {
+            (ApiChangeKind::Removed, ChangeSeverity::AbiBreaking, "declaration removed")
+        };
+        changes.push(change(kind, severity, declaration, Some(declaration), None, message.to_string()));
+    }
+    for declaration in unmatched_new {
+        let (kind, severity, message) = classify_addition(declaration, new_api);
+        changes.push(change(kind, severity, declaration, None, Some(declaration), message));
+    }
+
+    let severity = changes.iter().map(|change| change.severity).max();
+    let old_version = marketing_version(old);
+    let new_version = marketing_version(new);
+    let semver = semver_verdict(severity, old_version.as_deref(), new_version.as_deref());
+    ApiDiff { old_version, new_version, severity, changes, semver }
+}
+
+fn same_declaration(old: &ApiDeclaration, new: &ApiDeclaration) -> bool {
+    old.kind == new.kind && old.name == new.name && old.container == new.container
+}
+
+fn change(
+    kind: ApiChangeKind,
+    severity: ChangeSeverity,
+    declaration: &ApiDeclaration,
+    old: Option<&ApiDeclaration>,
+    new: Option<&ApiDeclaration>,
+    message: String,
+) -> ApiChange {
+    ApiChange {
+        kind,
+        severity,
+        declaration_kind: declaration.kind,
+        name: declaration.name.clone(),
+        old_signature: old.map(|declaration| declaration.signature.clone()),
+        new_signature: new.map(|declaration| declaration.signature.clone()),
+        message,
+    }
+}
+
+fn compare_declarations(old: &ApiDeclaration, new: &ApiDeclaration, old_slices: &[&str], new_slices: &[&str], changes: &mut Vec<ApiChange>) {
+    if old.signature != new.signature {
+        // Adding accessors (e.g. a setter) is additive, removing them is not.
+        let (old_base, old_accessors) = split_accessors(&old.signature);
+        let (new_base, new_accessors) = split_accessors(&new.signature);
+        let removed_accessors: Vec<&str> = old_accessors.iter().copied().filter(|accessor| !new_accessors.contains(accessor)).collect();
+        let (severity, message) = if old_base != new_base {
+            (ChangeSeverity::AbiBreaking, "signature changed".to_string())
+        } else if !removed_accessors.is_empty() {
+            (ChangeSeverity::AbiBreaking, format!("accessors removed: {}", removed_accessors.join(", ")))
+        } else {
+            (ChangeSeverity::Additive, "accessors added".to_string())
+        };
+        changes.push(change(ApiChangeKind::SignatureChanged, severity, new, Some(old), Some(new), message));
+    }
+
+    // Clients inline the layout of frozen types, so it cannot change once published;
+    // freezing a type is allowed.
+    let frozen = |declaration: &ApiDeclaration| declaration.attributes.iter().any(|attribute| attribute == "@frozen");
+    match (frozen(old), frozen(new)) {
+        (true, false) => changes.push(change(
+            ApiChangeKind::FrozenRemoved,
+            ChangeSeverity::AbiBreaking,
+            new,
+            Some(old),
+            Some(new),
+            "@frozen removed".to_string(),
+        )),
+        (false, true) => changes.push(change(
+            ApiChangeKind::FrozenAdded,
+            ChangeSeverity::Additive,
+            new,
+            Some(old),
+            Some(new),
+            "@frozen added".to_string(),
+        )),
+        _ => {}
+    }
+
+    let tightened = tightened_availability(&availability(&old.availability), &availability(&new.availability));
+    if !tightened.is_empty() {
+        changes.push(change(
+            ApiChangeKind::AvailabilityTightened,
+            ChangeSeverity::SourceBreaking,
+            new,
+            Some(old),
+            Some(new),
+            format!("availability tightened: {}", tightened.join(", ")),
+        ));
+    }
+
+    // An empty list of identifiers stands for every slice.
+    let declared_in = |declaration: &ApiDeclaration, all: &[&str]| -> Vec<String> {
+        if declaration.library_identifiers.is_empty() {
+            all.iter().map(|slice| slice.to_string()).collect()
+        } else {
+            declaration.library_identifiers.clone()
+        }
+    };
+    let new_declared_in = declared_in(new, new_slices);
+    let lost: Vec<String> = declared_in(old, old_slices)
+        .into_iter()
+        .filter(|slice| new_slices.contains(&slice.as_str()) && !new_declared_in.contains(slice))
+        .collect();
+    if !lost.is_empty() {
+        changes.push(change(
+            ApiChangeKind::RemovedFromSlices,
+            ChangeSeverity::AbiBreaking,
+            new,
+            Some(old),
+            Some(new),
+            format!("no longer declared in {}", lost.join(", ")),
+        ));
+    }
+}
+
+fn classify_addition(declaration: &ApiDeclaration, api: &[ApiDeclaration]) -> (ApiChangeKind, ChangeSeverity, String) {
+    if declaration.container == Some(DeclarationKind::Protocol) {
+        // Associated types provide their default inline (`associatedtype Item = Int`).
+        let has_default = match declaration.kind {
+            DeclarationKind::AssociatedType => declaration.signature.contains('='),
+            _ => api
+                .iter()
+                .any(|other| other.container == Some(DeclarationKind::Extension) && other.name == declaration.name),
+        };
+        if !has_default {
+            return (
+                ApiChangeKind::AddedRequirement,
+                ChangeSeverity::AbiBreaking,
+                "protocol requirement added without a default implementation".to_string(),
+            );
+        }
+    }
+    if declaration.kind == DeclarationKind::Case {
+        // Clients switch exhaustively over frozen enums.
+        let frozen_parent = parent_name(&declaration.name).is_some_and(|parent| {
+            api.iter().any(|other| {
+                other.kind == DeclarationKind::Enum && other.name == parent && other.attributes.iter().any(|attribute| attribute == "@frozen")
+            })
+        });
+        if frozen_parent {
+            return (ApiChangeKind::Added, ChangeSeverity::AbiBreaking, "case added to a @frozen enum".to_string());
+        }
+    }
+    (ApiChangeKind::Added, ChangeSeverity::Additive, "declaration added".to_string())
+}
+
+/// Whether `name` is a requirement of a protocol in `api`.
+fn is_requirement(api: &[ApiDeclaration], name: &str) -> bool {
+    api.iter().any(|declaration| declaration.container == Some(DeclarationKind::Protocol) && declaration.name == name)
+}
+
+/// Qualified name of the type enclosing a member (`Mood` for `Mood.other(_:reason:)`).
+fn parent_name(name: &str) -> Option<&str> {
+    let end = name.find('(').unwrap_or(name.len());
+    name[..end].rsplit_once('.').map(|(parent, _)| parent)
+}
+
+/// Splits the `{ get set }` accessors off a property or subscript signature.
+fn split_accessors(signature: &str) -> (&str, Vec<&str>) {
+    match signature.strip_suffix(" }").and_then(|rest| rest.rsplit_once(" { ")) {
+        Some((base, accessors)) => (base, accessors.split_whitespace().collect()),
+        None => (signature, Vec::new()),
+    }
+}
+
+/// Availability of a declaration on one platform (`*` for every platform).
+#[derive(Debug, Default)]
+struct PlatformAvailability {
+    platform: String,
+    introduced: Option<String>,
+    obsoleted: Option<String>,
+    unavailable: bool,
+}
+
+/// Parses `@available` attributes, in both their shorthand (`@available(iOS 15.0, *)`)
+/// and long (`@available(iOS, introduced: 15.0, deprecated: 16.0)`) forms.
+fn availability(attributes: &[String]) -> Vec<PlatformAvailability> {
+    let mut platforms = Vec::new();
+    for attribute in attributes {
+        let Some(arguments) = attribute.strip_prefix("@available(").and_then(|rest| rest.strip_suffix(')')) else {
+            continue;
+        };
+        let arguments: Vec<&str> = split_top_level(arguments, ',').into_iter().map(str::trim).collect();
+        let is_long_form = arguments.get(1).is_some_and(|argument| {
+            let name = argument.split(':').next().unwrap_or_default().trim();
+            matches!(name, "introduced" | "deprecated" | "obsoleted" | "unavailable" | "message" | "renamed" | "noasync")
+        });
+        if is_long_form {
+            let mut availability = PlatformAvailability { platform: arguments[0].to_string(), ..Default::default() };
+            for argument in &arguments[1..] {
+                match argument.split_once(':').map(|(name, value)| (name.trim(), value.trim())) {
+                    Some(("introduced", version)) => availability.introduced = Some(version.to_string()),
+                    Some(("obsoleted", version)) => availability.obsoleted = Some(version.to_string()),
+                    None if *argument == "unavailable" => availability.unavailable = true,
+                    _ => {}
+                }
+            }
+            platforms.push(availability);
+        } else {
+            for argument in arguments {
+                if let Some((platform, version)) = argument.split_once(' ') {
+                    platforms.push(PlatformAvailability {
+                        platform: platform.to_string(),
+                        introduced: Some(version.trim().to_string()),
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+    }
+    platforms
+}
+
+/// Describes the platforms where the new availability is narrower than the old one.
+fn tightened_availability(old: &[PlatformAvailability], new: &[PlatformAvailability]) -> Vec<String> {
+    let mut tightened = Vec::new();
+    for new in new {
+        let old = old.iter().find(|old| old.platform == new.platform);
+        if new.unavailable && !old.is_some_and(|old| old.unavailable) {
+            tightened.push(format!("unavailable on {}", new.platform));
+        }
+        if let Some(introduced) = &new.introduced {
+            let old_introduced = old.and_then(|old| old.introduced.as_deref());
+            if old_introduced.is_none_or(|old_introduced| compare_versions(introduced, old_introduced) == Ordering::Greater) {
+                tightened.push(format!("{} {} -> {}", new.platform, old_introduced.unwrap_or("any"), introduced));
+            }
+        }
+        if let Some(obsoleted) = &new.obsoleted {
+            let old_obsoleted = old.and_then(|old| old.obsoleted.as_deref());
+            if old_obsoleted.is_none_or(|old_obsoleted| compare_versions(obsoleted, old_obsoleted) == Ordering::Less) {
+                tightened.push(format!("obsoleted on {} {}", new.platform, obsoleted));
+            }
+        }
+    }
+    tightened
+}
+
+fn slices(framework: &FrameworkInfo) -> Vec<&str> {
+    framework
+        .available_libraries
+        .iter()
+        .flatten()
+        .map(|library| library.library_identifier.as_str())
+        .collect()
+}
+
+fn marketing_version(framework: &FrameworkInfo) -> Option<String> {
+    framework.available_libraries.iter().flatten().find_map(|library| library.marketing_version.clone())
+}
+
+/// Checks the version bump against the changes. Following semantic versioning, breaking
+/// changes call for a major bump and additions for a minor one; before 1.0, breaking
+/// changes only call for a minor bump and additions for a patch one.
+fn semver_verdict(severity: Option<ChangeSeverity>, old: Option<&str>, new: Option<&str>) -> SemverVerdict {
+    let pre_release = old.is_some_and(|old| version_components(old)[0] == 0);
+    let required_bump = match (severity, pre_release) {
+        (None, _) => VersionBump::None,
+        (Some(ChangeSeverity::Additive), false) => VersionBump::Minor,
+        (Some(ChangeSeverity::Additive), true) => VersionBump::Patch,
+        (Some(_), false) => VersionBump::Major,
+        (Some(_), true) => VersionBump::Minor,
+    };
+    let actual_bump = old.zip(new).map(|(old, new)| version_bump(old, new));
+    SemverVerdict {
+        required_bump,
+        actual_bump,
+        justified: required_bump == VersionBump::None || actual_bump.is_some_and(|actual| actual >= required_bump),
+    }
+}
+
+fn version_bump(old: &str, new: &str) -> VersionBump {
+    if compare_versions(new, old) == Ordering::Less {
+        return VersionBump::Downgrade;
+    }
+    let (old, new) = (version_components(old), version_components(new));
+    if new[0] > old[0] {
+        VersionBump::Major
+    } else if new[1] > old[1] {
+        VersionBump::Minor
+    } else if new[2] > old[2] {
+        VersionBump::Patch
+    } else {
+        VersionBump::None
+    }
+}
+
+/// Major, minor and patch components of a version, missing ones being 0.
+fn version_components(version: &str) -> [u64; 3] {
+    let mut components = [0; 3];
+    for (component, part) in components.iter_mut().zip(version.split('.')) {
+        *component = part.trim().parse().unwrap_or(0);
+    }
+    components
+}
+
+impl fmt::Display for ChangeSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ChangeSeverity::Additive => "additive",
+            ChangeSeverity::SourceBreaking => "source-breaking",
+            ChangeSeverity::AbiBreaking => "ABI-breaking",
+        })
+    }
+}
+
+impl fmt::Display for VersionBump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            VersionBump::Downgrade => "downgrade",
+            VersionBump::None => "none",
+            VersionBump::Patch => "patch",
+            VersionBump::Minor => "minor",
+            VersionBump::Major => "major",
+        })
+    }
+}
+
+impl fmt::Display for ApiDiff {
+    /// Human-readable form of the diff.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let version = |version: &Option<String>| version.clone().unwrap_or_else(|| "unknown".to_string());
+        writeln!(f, "{} -> {}", version(&self.old_version), version(&self.new_version))?;
+        if self.changes.is_empty() {
+            writeln!(f, "No API changes")?;
+        }
+        for change in &self.changes {
+            writeln!(f, "[{}] {}: {}", change.severity, change.name, change.message)?;
+            match (&change.old_signature, &change.new_signature) {
+                (Some(old), Some(new)) if old == new => writeln!(f, "  {}", new)?,
+                (old, new) => {
+                    if let Some(old) = old {
+                        writeln!(f, "  - {}", old)?;
+                    }
+                    if let Some(new) = new {
+                        writeln!(f, "  + {}", new)?;
+                    }
+                }
+            }
+        }
+        let actual = self.semver.actual_bump.map_or("unknown".to_string(), |bump| bump.to_string());
+        let verdict = if self.semver.justified { "justified" } else { "not justified" };
+        writeln!(f, "\nRequired bump: {}, actual bump: {} ({})", self.semver.required_bump, actual, verdict)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework_info::LibraryInfo;
+
+    fn declaration(kind: DeclarationKind, name: &str, signature: &str) -> ApiDeclaration {
+        ApiDeclaration {
+            kind,
+            name: name.to_string(),
+            signature: signature.to_string(),
+            container: None,
+            generic_signature: None,
+            availability: Vec::new(),
+            attributes: Vec::new(),
+            library_identifiers: Vec::new(),
+        }
+    }
+
+    fn member(container: DeclarationKind, kind: DeclarationKind, name: &str, signature: &str) -> ApiDeclaration {
+        ApiDeclaration { container: Some(container), ..declaration(kind, name, signature) }
+    }
+
+    fn framework(version: &str, api: Vec<ApiDeclaration>) -> FrameworkInfo {
+        let library = |identifier: &str| LibraryInfo {
+            library_identifier: identifier.to_string(),
+            marketing_version: Some(version.to_string()),
+            ..Default::default()
+        };
+        FrameworkInfo {
+            available_libraries: Some(vec![library("ios-arm64"), library("ios-arm64_x86_64-simulator")]),
+            api: Some(api),
+            ..Default::default()
+        }
+    }
+
+    fn diff(old: Vec<ApiDeclaration>, new: Vec<ApiDeclaration>) -> ApiDiff {
+        diff_api(&framework("1.0.0", old), &framework("2.0.0", new))
+    }
+
+    fn only_change(diff: &ApiDiff) -> (ApiChangeKind, ChangeSeverity) {
+        assert_eq!(diff.changes.len(), 1, "{:?}", diff.changes);
+        (diff.changes[0].kind, diff.changes[0].severity)
+    }
+
+    #[test]
+    fn unchanged_api_has_no_changes() {
+        let api = vec![declaration(DeclarationKind::Func, "greet(name:)", "public func greet(name: Swift.String)")];
+        let diff = diff(api.clone(), api);
+        assert!(diff.changes.is_empty());
+        assert_eq!(diff.severity, None);
+        assert_eq!(diff.semver.required_bump, VersionBump::None);
+    }
+
+    #[test]
+    fn classifies_additions_and_removals() {
+        let greet = declaration(DeclarationKind::Func, "greet()", "public func greet()");
+        let added = diff(Vec::new(), vec![greet.clone()]);
+        assert_eq!(only_change(&added), (ApiChangeKind::Added, ChangeSeverity::Additive));
+        let removed = diff(vec![greet], Vec::new());
+        assert_eq!(only_change(&removed), (ApiChangeKind::Removed, ChangeSeverity::AbiBreaking));
+    }
+
+    #[test]
+    fn removing_an_unavailable_declaration_is_not_breaking() {
+        let mut greet = declaration(DeclarationKind::Func, "greet()", "public func greet()");
+        greet.availability = vec!["@available(*, unavailable)".to_string()];
+        assert_eq!(only_change(&diff(vec![greet.clone()], Vec::new())), (ApiChangeKind::Removed, ChangeSeverity::Additive));
+
+        // Still usable on the other platforms.
+        greet.availability = vec!["@available(macOS, unavailable)".to_string()];
+        assert_eq!(only_change(&diff(vec![greet], Vec::new())), (ApiChangeKind::Removed, ChangeSeverity::AbiBreaking));
+    }
+
+    #[test]
+    fn classifies_signature_and_accessor_changes() {
+        let old = declaration(DeclarationKind::Var, "Greeter.name", "public var name: Swift.String { get }");
+        let setter = declaration(DeclarationKind::Var, "Greeter.name", "public var name: Swift.String { get set }");
+        let retyped = declaration(DeclarationKind::Var, "Greeter.name", "public var name: Swift.Int { get }");
+
+        let added = diff(vec![old.clone()], vec![setter.clone()]);
+        assert_eq!(only_change(&added), (ApiChangeKind::SignatureChanged, ChangeSeverity::Additive));
+        let removed = diff(vec![setter], vec![old.clone()]);
+        assert_eq!(only_change(&removed), (ApiChangeKind::SignatureChanged, ChangeSeverity::AbiBreaking));
+        assert_eq!(removed.changes[0].message, "accessors removed: set");
+        let changed = diff(vec![old], vec![retyped]);
+        assert_eq!(only_change(&changed), (ApiChangeKind::SignatureChanged, ChangeSeverity::AbiBreaking));
+        assert_eq!(changed.changes[0].message, "signature changed");
+    }
+
+    #[test]
+    fn protocol_requirements_need_a_default_implementation() {
+        let protocol = declaration(DeclarationKind::Protocol, "Greeting", "public protocol Greeting");
+        let requirement = member(DeclarationKind::Protocol, DeclarationKind::Func, "Greeting.greet()", "func greet()");
+        let default = member(DeclarationKind::Extension, DeclarationKind::Func, "Greeting.greet()", "public func greet()");
+
+        let without_default = diff(vec![protocol.clone()], vec![protocol.clone(), requirement.clone()]);
+        assert_eq!(only_change(&without_default), (ApiChangeKind::AddedRequirement, ChangeSeverity::AbiBreaking));
+
+        let with_default = diff(vec![protocol.clone()], vec![protocol.clone(), requirement.clone(), default.clone()]);
+        assert!(with_default.changes.iter().all(|change| change.kind == ApiChangeKind::Added));
+        assert_eq!(with_default.severity, Some(ChangeSeverity::Additive));
+
+        let removed_default = diff(vec![protocol.clone(), requirement.clone(), default], vec![protocol, requirement]);
+        assert_eq!(only_change(&removed_default), (ApiChangeKind::RemovedDefaultImplementation, ChangeSeverity::AbiBreaking));
+    }
+
+    #[test]
+    fn associated_types_with_a_default_are_additive() {
+        let protocol = declaration(DeclarationKind::Protocol, "Greeting", "public protocol Greeting");
+        let with_default = member(DeclarationKind::Protocol, DeclarationKind::AssociatedType, "Greeting.Name", "associatedtype Name = Swift.String");
+        let without_default = member(DeclarationKind::Protocol, DeclarationKind::AssociatedType, "Greeting.Name", "associatedtype Name");
+
+        let additive = diff(vec![protocol.clone()], vec![protocol.clone(), with_default]);
+        assert_eq!(only_change(&additive), (ApiChangeKind::Added, ChangeSeverity::Additive));
+        let breaking = diff(vec![protocol.clone()], vec![protocol, without_default]);
+        assert_eq!(only_change(&breaking), (ApiChangeKind::AddedRequirement, ChangeSeverity::AbiBreaking));
+    }
+
+    #[test]
+    fn frozen_types_cannot_change_layout() {
+        let mood = declaration(DeclarationKind::Enum, "Mood", "public enum Mood");
+        let frozen_mood = ApiDeclaration { attributes: vec!["@frozen".to_string()], ..mood.clone() };
+        let case = member(DeclarationKind::Enum, DeclarationKind::Case, "Mood.happy", "case happy");
+
+        let frozen = diff(vec![mood.clone()], vec![frozen_mood.clone()]);
+        assert_eq!(only_change(&frozen), (ApiChangeKind::FrozenAdded, ChangeSeverity::Additive));
+        let unfrozen = diff(vec![frozen_mood.clone()], vec![mood.clone()]);
+        assert_eq!(only_change(&unfrozen), (ApiChangeKind::FrozenRemoved, ChangeSeverity::AbiBreaking));
+
+        let frozen_case = diff(vec![frozen_mood.clone()], vec![frozen_mood, case.clone()]);
+        assert_eq!(only_change(&frozen_case), (ApiChangeKind::Added, ChangeSeverity::AbiBreaking));
+        let open_case = diff(vec![mood.clone()], vec![mood, case]);
+        assert_eq!(only_change(&open_case), (ApiChangeKind::Added, ChangeSeverity::Additive));
+    }
+
+    #[test]
+    fn tightened_availability_is_source_breaking() {
+        let available = |attributes: &[&str]| ApiDeclaration {
+            availability: attributes.iter().map(|attribute| attribute.to_string()).collect(),
+            ..declaration(DeclarationKind::Func, "greet()", "public func greet()")
+        };
+
+        let raised = diff(vec![available(&["@available(iOS 13.0, *)"])], vec![available(&["@available(iOS 15.0, *)"])]);
+        assert_eq!(only_change(&raised), (ApiChangeKind::AvailabilityTightened, ChangeSeverity::SourceBreaking));
+        assert_eq!(raised.changes[0].message, "availability tightened: iOS 13.0 -> 15.0");
+
+        let lowered = diff(vec![available(&["@available(iOS 15.0, *)"])], vec![available(&["@available(iOS 13.0, *)"])]);
+        assert!(lowered.changes.is_empty());
+
+        let unavailable = diff(vec![available(&[])], vec![available(&["@available(tvOS, unavailable)"])]);
+        assert_eq!(only_change(&unavailable), (ApiChangeKind::AvailabilityTightened, ChangeSeverity::SourceBreaking));
+
+        let obsoleted = diff(vec![available(&[])], vec![available(&["@available(iOS, introduced: 13.0, obsoleted: 17.0)"])]);
+        assert_eq!(only_change(&obsoleted), (ApiChangeKind::AvailabilityTightened, ChangeSeverity::SourceBreaking));
+    }
+
+    #[test]
+    fn removal_from_a_slice_is_abi_breaking() {
+        let greet = declaration(DeclarationKind::Func, "greet()", "public func greet()");
+        let device_only = ApiDeclaration { library_identifiers: vec!["ios-arm64".to_string()], ..greet.clone() };
+        let diff = diff(vec![greet], vec![device_only]);
+        assert_eq!(only_change(&diff), (ApiChangeKind::RemovedFromSlices, ChangeSeverity::AbiBreaking));
+        assert_eq!(diff.changes[0].message, "no longer declared in ios-arm64_x86_64-simulator");
+    }
+
+    #[test]
+    fn semver_requires_major_bumps_for_breaking_changes() {
+        let verdict = semver_verdict(Some(ChangeSeverity::AbiBreaking), Some("1.2.0"), Some("1.3.0"));
+        assert_eq!((verdict.required_bump, verdict.actual_bump, verdict.justified), (VersionBump::Major, Some(VersionBump::Minor), false));
+        assert!(semver_verdict(Some(ChangeSeverity::SourceBreaking), Some("1.2.0"), Some("2.0")).justified);
+        assert!(semver_verdict(Some(ChangeSeverity::Additive), Some("1.2.0"), Some("1.3.0")).justified);
+        assert!(!semver_verdict(Some(ChangeSeverity::Additive), Some("1.2.0"), Some("1.2.1")).justified);
+        assert!(!semver_verdict(Some(ChangeSeverity::Additive), Some("1.2.0"), None).justified);
+        assert!(semver_verdict(None, None, None).justified);
+        assert_eq!(version_bump("2.0.0", "1.9.0"), VersionBump::Downgrade);
+    }
+
+    #[test]
+    fn semver_relaxes_bumps_before_1_0() {
+        let breaking = semver_verdict(Some(ChangeSeverity::AbiBreaking), Some("0.4.2"), Some("0.5.0"));
+        assert_eq!(breaking.required_bump, VersionBump::Minor);
+        assert!(breaking.justified);
+        let additive = semver_verdict(Some(ChangeSeverity::Additive), Some("0.4.2"), Some("0.4.3"));
+        assert_eq!(additive.required_bump, VersionBump::Patch);
+        assert!(additive.justified);
+        assert!(!semver_verdict(Some(ChangeSeverity::AbiBreaking), Some("0.4.2"), Some("0.4.3")).justified);
+    }
+
+    #[test]
+    fn only_unjustified_breaking_changes_fail() {
+        let greet = declaration(DeclarationKind::Func, "greet()", "public func greet()");
+        let breaking = diff_api(&framework("1.0.0", vec![greet.clone()]), &framework("1.1.0", Vec::new()));
+        assert!(breaking.is_unjustified_break());
+        let major = diff_api(&framework("1.0.0", vec![greet.clone()]), &framework("2.0.0", Vec::new()));
+        assert!(!major.is_unjustified_break());
+        // Additions without a bump are not justified, but do not break clients.
+        let additive = diff_api(&framework("1.0.0", Vec::new()), &framework("1.0.0", vec![greet]));
+        assert!(!additive.semver.justified);
+        assert!(!additive.is_unjustified_break());
+    }
+}
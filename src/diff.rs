@@ -0,0 +1,380 @@
+use std::fmt;
+use serde::Serialize;
+use crate::framework_info::{FrameworkInfo, LibraryInfo, Linkage, PrivacyInfo};
+use crate::utils::{ensure_unique_ordered, format_size};
+
+/// Differences between two versions of a framework.
+#[derive(Serialize, Debug, Default)]
+pub struct FrameworkDiff {
+    pub old_name: Option<String>,
+    pub new_name: Option<String>,
+    /// Library identifiers only present in the new version.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added_slices: Vec<String>,
+    /// Library identifiers only present in the old version.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed_slices: Vec<String>,
+    /// Platforms (e.g. `ios-simulator`) only supported by the new version.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added_platforms: Vec<String>,
+    /// Platforms only supported by the old version.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed_platforms: Vec<String>,
+    /// Framework-wide values that changed (Swift compiler, linkage...).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<ValueChange>,
+    /// Slices present in both versions that changed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub slices: Vec<SliceDiff>,
+}
+
+/// A value that differs between the two versions; `None` when it is absent.
+#[derive(Serialize, Debug, Clone)]
+pub struct ValueChange {
+    pub field: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// Differences of a slice present in both versions, matched by library identifier or platform.
+#[derive(Serialize, Debug, Default)]
+pub struct SliceDiff {
+    /// Identifier of the slice in the new version.
+    pub library_identifier: String,
+    pub platform: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<ValueChange>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added_architectures: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed_architectures: Vec<String>,
+    /// Swift imports only present in the new version.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added_dependencies: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed_dependencies: Vec<String>,
+    /// Install names of linked libraries only present in the new version.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added_linked_libraries: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed_linked_libraries: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub privacy: Option<PrivacyDiff>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<SizeDelta>,
+}
+
+/// Changes to the privacy manifest of a slice.
+#[derive(Serialize, Debug, Default)]
+pub struct PrivacyDiff {
+    /// Presence of the manifest and `NSPrivacyTracking`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<ValueChange>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added_tracking_domains: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed_tracking_domains: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added_collected_data_types: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed_collected_data_types: Vec<String>,
+    /// Collected data types whose linkage, tracking or purposes changed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changed_collected_data_types: Vec<ValueChange>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added_accessed_api_types: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed_accessed_api_types: Vec<String>,
+    /// Accessed API types whose reasons changed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changed_accessed_api_types: Vec<ValueChange>,
+}
+
+/// Size of the slice binary in both versions, in bytes.
+#[derive(Serialize, Debug)]
+pub struct SizeDelta {
+    pub old: u64,
+    pub new: u64,
+    pub delta: i64,
+}
+
+impl FrameworkDiff {
+    /// Whether both versions are equivalent for every compared value.
+    pub fn is_empty(&self) -> bool {
+        self.added_slices.is_empty()
+            && self.removed_slices.is_empty()
+            && self.added_platforms.is_empty()
+            && self.removed_platforms.is_empty()
+            && self.changes.is_empty()
+            && self.slices.is_empty()
+    }
+}
+
+impl SliceDiff {
+    fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+            && self.added_architectures.is_empty()
+            && self.removed_architectures.is_empty()
+            && self.added_dependencies.is_empty()
+            && self.removed_dependencies.is_empty()
+            && self.added_linked_libraries.is_empty()
+            && self.removed_linked_libraries.is_empty()
+            && self.privacy.is_none()
+            && self.size.is_none()
+    }
+}
+
+impl PrivacyDiff {
+    fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+            && self.added_tracking_domains.is_empty()
+            && self.removed_tracking_domains.is_empty()
+            && self.added_collected_data_types.is_empty()
+            && self.removed_collected_data_types.is_empty()
+            && self.changed_collected_data_types.is_empty()
+            && self.added_accessed_api_types.is_empty()
+            && self.removed_accessed_api_types.is_empty()
+            && self.changed_accessed_api_types.is_empty()
+    }
+}
+
+/// Compares the reports of two versions of a framework.
+pub fn diff_frameworks(old: &FrameworkInfo, new: &FrameworkInfo) -> FrameworkDiff {
+    let mut diff = FrameworkDiff {
+        old_name: old.framework_name.clone(),
+        new_name: new.framework_name.clone(),
+        ..Default::default()
+    };
+
+    let changes = &mut diff.changes;
+    compare(changes, "framework_name", old.framework_name.clone(), new.framework_name.clone());
+    compare(changes, "swift_compiler_info", old.swift_compiler_info.clone(), new.swift_compiler_info.clone());
+    compare(changes, "swift_version", old.swift_version.clone(), new.swift_version.clone());
+    compare(changes, "library_evolution_enabled", Some(old.library_evolution_enabled.to_string()), Some(new.library_evolution_enabled.to_string()));
+    compare(changes, "built_for_distribution", Some(old.built_for_distribution.to_string()), Some(new.built_for_distribution.to_string()));
+    compare(changes, "is_mergeable", Some(old.is_mergeable.to_string()), Some(new.is_mergeable.to_string()));
+    compare(changes, "linkage", old.linkage.map(linkage_name), new.linkage.map(linkage_name));
+
+    let old_libraries = old.available_libraries.as_deref().unwrap_or_default();
+    let new_libraries = new.available_libraries.as_deref().unwrap_or_default();
+
+    // Slices keep their identifier across versions unless an architecture is added or removed,
+    // so fall back to the platform, which is unique within an xcframework.
+    let mut unmatched_old: Vec<&LibraryInfo> = old_libraries.iter().collect();
+    let mut pairs = Vec::new();
+    for library in new_libraries {
+        let position = unmatched_old
+            .iter()
+            .position(|old| old.library_identifier == library.library_identifier)
+            .or_else(|| unmatched_old.iter().position(|old| old.platform_name() == library.platform_name()));
+        match position {
+            Some(position) => pairs.push((unmatched_old.remove(position), library)),
+            None => diff.added_slices.push(library.library_identifier.clone()),
+        }
+    }
+    diff.removed_slices = unmatched_old.iter().map(|library| library.library_identifier.clone()).collect();
+
+    let old_platforms: Vec<String> = old_libraries.iter().map(LibraryInfo::platform_name).collect();
+    let new_platforms: Vec<String> = new_libraries.iter().map(LibraryInfo::platform_name).collect();
+    (diff.added_platforms, diff.removed_platforms) = added_removed(&old_platforms, &new_platforms);
+
+    diff.slices = pairs
+        .into_iter()
+        .map(|(old, new)| diff_slices(old, new))
+        .filter(|slice| !slice.is_empty())
+        .collect();
+    diff
+}
+
+fn diff_slices(old: &LibraryInfo, new: &LibraryInfo) -> SliceDiff {
+    let mut diff = SliceDiff {
+        library_identifier: new.library_identifier.clone(),
+        platform: new.platform_name(),
+        ..Default::default()
+    };
+
+    let changes = &mut diff.changes;
+    compare(changes, "library_identifier", Some(old.library_identifier.clone()), Some(new.library_identifier.clone()));
+    compare(changes, "marketing_version", old.marketing_version.clone(), new.marketing_version.clone());
+    compare(changes, "minimum_os_version", old.minimum_os_version.clone(), new.minimum_os_version.clone());
+    compare(changes, "linkage", old.linkage.map(linkage_name), new.linkage.map(linkage_name));
+    compare(changes, "swift_compiler_info", swift_compiler_info(old), swift_compiler_info(new));
+
+    (diff.added_architectures, diff.removed_architectures) =
+        added_removed(&old.supported_architectures, &new.supported_architectures);
+    (diff.added_dependencies, diff.removed_dependencies) = added_removed(&old.dependencies, &new.dependencies);
+    let install_names = |library: &LibraryInfo| -> Vec<String> {
+        library.linked_libraries.iter().map(|linked| linked.install_name.clone()).collect()
+    };
+    (diff.added_linked_libraries, diff.removed_linked_libraries) = added_removed(&install_names(old), &install_names(new));
+
+    let privacy = diff_privacy(old.privacy_info.as_ref(), new.privacy_info.as_ref());
+    diff.privacy = (!privacy.is_empty()).then_some(privacy);
+
+    if let (Some(old), Some(new)) = (old.size_bytes, new.size_bytes) {
+        if old != new {
+            diff.size = Some(SizeDelta { old, new, delta: new as i64 - old as i64 });
+        }
+    }
+    diff
+}
+
+fn diff_privacy(old: Option<&PrivacyInfo>, new: Option<&PrivacyInfo>) -> PrivacyDiff {
+    let mut diff = PrivacyDiff::default();
+    let present = |info: Option<&PrivacyInfo>| info.is_some_and(|info| info.present);
+    compare(&mut diff.changes, "present", Some(present(old).to_string()), Some(present(new).to_string()));
+    compare(
+        &mut diff.changes,
+        "tracking",
+        old.and_then(|info| info.tracking).map(|tracking| tracking.to_string()),
+        new.and_then(|info| info.tracking).map(|tracking| tracking.to_string()),
+    );
+
+    let tracking_domains = |info: Option<&PrivacyInfo>| -> Vec<String> {
+        info.and_then(|info| info.tracking_domains.clone()).unwrap_or_default()
+    };
+    (diff.added_tracking_domains, diff.removed_tracking_domains) =
+        added_removed(&tracking_domains(old), &tracking_domains(new));
+
+    // Entries are keyed by data type and API type; the rest of the entry is compared as a summary.
+    let collected_data_types = |info: Option<&PrivacyInfo>| -> Vec<(String, String)> {
+        info.and_then(|info| info.collected_data_types.as_ref())
+            .map(|types| {
+                types
+                    .iter()
+                    .map(|data_type| {
+                        let summary = format!(
+                            "linked: {}, tracking: {}, purposes: {}",
+                            data_type.linked_to_user,
+                            data_type.tracking,
+                            data_type.purposes.join(", ")
+                        );
+                        (data_type.data_type.clone(), summary)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    (diff.added_collected_data_types, diff.removed_collected_data_types, diff.changed_collected_data_types) =
+        diff_entries(&collected_data_types(old), &collected_data_types(new));
+
+    let accessed_api_types = |info: Option<&PrivacyInfo>| -> Vec<(String, String)> {
+        info.and_then(|info| info.accessed_api_types.as_ref())
+            .map(|types| types.iter().map(|api_type| (api_type.api.clone(), api_type.reasons.join(", "))).collect())
+            .unwrap_or_default()
+    };
+    (diff.added_accessed_api_types, diff.removed_accessed_api_types, diff.changed_accessed_api_types) =
+        diff_entries(&accessed_api_types(old), &accessed_api_types(new));
+    diff
+}
+
+/// Records a change of `field` if the values differ.
+fn compare(changes: &mut Vec<ValueChange>, field: &str, old: Option<String>, new: Option<String>) {
+    if old != new {
+        changes.push(ValueChange { field: field.to_string(), old, new });
+    }
+}
+
+/// Returns the items only in `new` and the items only in `old`.
+fn added_removed(old: &[String], new: &[String]) -> (Vec<String>, Vec<String>) {
+    let added = new.iter().filter(|item| !old.contains(item)).cloned().collect();
+    let removed = old.iter().filter(|item| !new.contains(item)).cloned().collect();
+    (ensure_unique_ordered(added), ensure_unique_ordered(removed))
+}
+
+/// Compares `(key, summary)` entries, returning the added and removed keys and the changed summaries.
+fn diff_entries(old: &[(String, String)], new: &[(String, String)]) -> (Vec<String>, Vec<String>, Vec<ValueChange>) {
+    let keys = |entries: &[(String, String)]| -> Vec<String> { entries.iter().map(|(key, _)| key.clone()).collect() };
+    let (added, removed) = added_removed(&keys(old), &keys(new));
+    let mut changed = Vec::new();
+    for (key, new_summary) in new {
+        if let Some((_, old_summary)) = old.iter().find(|(old_key, _)| old_key == key) {
+            compare(&mut changed, key, Some(old_summary.clone()), Some(new_summary.clone()));
+        }
+    }
+    (added, removed, changed)
+}
+
+fn linkage_name(linkage: Linkage) -> String {
+    match linkage {
+        Linkage::DynamicFramework => "dynamic_framework",
+        Linkage::DynamicLibrary => "dynamic_library",
+        Linkage::MergeableDynamicLibrary => "mergeable_dynamic_library",
+        Linkage::StaticFramework => "static_framework",
+        Linkage::StaticLibrary => "static_library",
+    }
+    .to_string()
+}
+
+/// Swift compiler of a slice, when all of its interfaces agree.
+fn swift_compiler_info(library: &LibraryInfo) -> Option<String> {
+    let mut compilers = library.swift_interfaces.iter().map(|interface| interface.compiler_info.clone());
+    let first = compilers.next()??;
+    compilers.all(|compiler| compiler.as_ref() == Some(&first)).then_some(first)
+}
+
+impl fmt::Display for FrameworkDiff {
+    /// Human-readable form of the diff.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = |name: &Option<String>| name.clone().unwrap_or_else(|| "unknown".to_string());
+        writeln!(f, "{} -> {}", name(&self.old_name), name(&self.new_name))?;
+        if self.is_empty() {
+            return writeln!(f, "No differences");
+        }
+        write_list(f, "", "added slices", &self.added_slices)?;
+        write_list(f, "", "removed slices", &self.removed_slices)?;
+        write_list(f, "", "added platforms", &self.added_platforms)?;
+        write_list(f, "", "removed platforms", &self.removed_platforms)?;
+        write_changes(f, "", &self.changes)?;
+
+        for slice in &self.slices {
+            writeln!(f, "\n{} ({}):", slice.library_identifier, slice.platform)?;
+            write_changes(f, "  ", &slice.changes)?;
+            write_list(f, "  ", "added architectures", &slice.added_architectures)?;
+            write_list(f, "  ", "removed architectures", &slice.removed_architectures)?;
+            write_list(f, "  ", "added dependencies", &slice.added_dependencies)?;
+            write_list(f, "  ", "removed dependencies", &slice.removed_dependencies)?;
+            write_list(f, "  ", "added linked libraries", &slice.added_linked_libraries)?;
+            write_list(f, "  ", "removed linked libraries", &slice.removed_linked_libraries)?;
+            if let Some(size) = &slice.size {
+                let sign = if size.delta < 0 { "-" } else { "+" };
+                writeln!(
+                    f,
+                    "  size: {} -> {} ({}{})",
+                    format_size(size.old),
+                    format_size(size.new),
+                    sign,
+                    format_size(size.delta.unsigned_abs())
+                )?;
+            }
+            if let Some(privacy) = &slice.privacy {
+                writeln!(f, "  privacy manifest:")?;
+                write_changes(f, "    ", &privacy.changes)?;
+                write_list(f, "    ", "added tracking domains", &privacy.added_tracking_domains)?;
+                write_list(f, "    ", "removed tracking domains", &privacy.removed_tracking_domains)?;
+                write_list(f, "    ", "added collected data types", &privacy.added_collected_data_types)?;
+                write_list(f, "    ", "removed collected data types", &privacy.removed_collected_data_types)?;
+                write_changes(f, "    ", &privacy.changed_collected_data_types)?;
+                write_list(f, "    ", "added accessed API types", &privacy.added_accessed_api_types)?;
+                write_list(f, "    ", "removed accessed API types", &privacy.removed_accessed_api_types)?;
+                write_changes(f, "    ", &privacy.changed_accessed_api_types)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_list(f: &mut fmt::Formatter<'_>, indent: &str, title: &str, items: &[String]) -> fmt::Result {
+    if items.is_empty() {
+        return Ok(());
+    }
+    writeln!(f, "{}{}: {}", indent, title, items.join(", "))
+}
+
+fn write_changes(f: &mut fmt::Formatter<'_>, indent: &str, changes: &[ValueChange]) -> fmt::Result {
+    let value = |value: &Option<String>| value.as_deref().unwrap_or("none").to_string();
+    for change in changes {
+        writeln!(f, "{}{}: {} -> {}", indent, change.field, value(&change.old), value(&change.new))?;
+    }
+    Ok(())
+}
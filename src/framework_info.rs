@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct FrameworkInfo {
     pub framework_name: Option<String>,
     pub is_mergeable: bool,
@@ -10,14 +10,30 @@ pub struct FrameworkInfo {
     pub library_evolution_enabled: bool,
     pub built_for_distribution: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub linkage: Option<Linkage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embed: Option<Embedding>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub available_libraries: Option<Vec<LibraryInfo>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<SignatureInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sdk_requirements: Option<SdkRequirements>,
+    /// Public API of the Swift module, only collected on demand (`--api`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api: Option<Vec<ApiDeclaration>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<Warning>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct LibraryInfo {
     pub binary_path: String,
     pub library_identifier: String,
     pub library_path: String,
+    /// `CFBundleIdentifier` of the library's Info.plist.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bundle_identifier: Option<String>,
     pub marketing_version: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mergeable_metadata: Option<bool>,
@@ -29,10 +45,418 @@ pub struct LibraryInfo {
     pub minimum_os_version: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub size: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub linkage: Option<Linkage>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub dependencies: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub linked_libraries: Vec<LinkedLibrary>,
+    /// Effective privacy manifest, merged from every manifest of the slice.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub privacy_info: Option<PrivacyInfo>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub privacy_manifests: Vec<PrivacyManifest>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub required_reason_apis: Vec<RequiredReasonApiUsage>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub swift_interfaces: Vec<SwiftInterfaceInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clang_module: Option<ClangModuleInfo>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub architectures: Vec<ArchitectureInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<SignatureInfo>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<Warning>,
+}
+
+impl LibraryInfo {
+    /// Platform of the slice, including its variant (e.g. `ios-simulator`).
+    pub fn platform_name(&self) -> String {
+        match &self.supported_platform_variant {
+            Some(variant) => format!("{}-{}", self.supported_platform, variant),
+            None => self.supported_platform.clone(),
+        }
+    }
+}
+
+/// How a slice binary is linked into an app.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Linkage {
+    /// `MH_DYLIB` inside a `.framework` bundle.
+    DynamicFramework,
+    /// `MH_DYLIB` outside of a bundle (e.g. `libFoo.dylib`).
+    DynamicLibrary,
+    /// `MH_DYLIB` built with mergeable metadata (Xcode 15 mergeable libraries).
+    MergeableDynamicLibrary,
+    /// Static archive or `MH_OBJECT` inside a `.framework` bundle.
+    StaticFramework,
+    /// Static archive or `MH_OBJECT` outside of a bundle (e.g. `libFoo.a`).
+    StaticLibrary,
+}
+
+impl Linkage {
+    /// Returns the Xcode "Frameworks, Libraries, and Embedded Content" setting for this linkage.
+    pub fn embedding(&self) -> Embedding {
+        match self {
+            // Mergeable libraries are embedded too; Xcode drops the binary when it merges them.
+            Linkage::DynamicFramework | Linkage::DynamicLibrary | Linkage::MergeableDynamicLibrary => Embedding::EmbedAndSign,
+            Linkage::StaticFramework | Linkage::StaticLibrary => Embedding::DoNotEmbed,
+        }
+    }
+}
+
+/// Xcode embedding setting recommended for a framework.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Embedding {
+    EmbedAndSign,
+    DoNotEmbed,
+}
+
+/// Dynamic library a slice binary links against, from its `LC_*_DYLIB` load commands.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LinkedLibrary {
+    pub install_name: String,
+    pub kind: LinkKind,
+    pub current_version: String,
+    pub compatibility_version: String,
+    /// Architectures of the binary that carry this load command.
+    pub architectures: Vec<String>,
+}
+
+/// Load command a linked library comes from.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkKind {
+    /// `LC_LOAD_DYLIB`
+    Load,
+    /// `LC_LOAD_WEAK_DYLIB`
+    Weak,
+    /// `LC_REEXPORT_DYLIB`
+    Reexport,
+    /// `LC_LOAD_UPWARD_DYLIB`
+    Upward,
+}
+
+/// Header of the `.swiftinterface` of one architecture of a slice.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SwiftInterfaceInfo {
+    /// Path of the interface, relative to the slice.
+    pub path: String,
+    pub architecture: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compiler_info: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compiler_version: Option<String>,
+    pub flags: SwiftModuleFlags,
+}
+
+/// Flags of the `// swift-module-flags:` and `// swift-module-flags-ignorable:` lines.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SwiftModuleFlags {
+    /// Target triple the interface was emitted for (e.g. `arm64-apple-ios15.0-simulator`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_variant: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module_abi_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub package_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_module_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub swift_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub optimization: Option<OptimizationLevel>,
+    pub library_evolution: bool,
+    pub testing: bool,
+    pub bare_slash_regex: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strict_concurrency: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enforce_exclusivity: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub upcoming_features: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub experimental_features: Vec<String>,
+    /// Flags not covered above, as written in the interface.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub other: Vec<String>,
+}
+
+/// Swift optimization level of an interface (`-Onone`, `-O`, `-Osize`, `-Ounchecked`).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OptimizationLevel {
+    /// `-Onone`, i.e. a debug build.
+    None,
+    Speed,
+    Size,
+    Unchecked,
+}
+
+/// Objective-C/C interface of a slice: its module maps and headers.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ClangModuleInfo {
+    /// Paths of the module maps, relative to the slice.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub module_maps: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub modules: Vec<ClangModule>,
+    /// Headers of the `Headers` directory, relative to it.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub public_headers: Vec<String>,
+    /// Headers of the `PrivateHeaders` directory, relative to it.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub private_headers: Vec<String>,
+}
+
+/// Module declared in a `module.modulemap`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ClangModule {
+    /// Module name, `*` for inferred submodules.
+    pub name: String,
+    pub framework: bool,
+    pub explicit: bool,
+    /// Attributes such as `system` or `extern_c`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub requires: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub umbrella_header: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub umbrella_directory: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub headers: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub private_headers: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub excluded_headers: Vec<String>,
+    /// Re-exported modules, `*` for every imported one.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exports: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub link: Vec<ModuleLink>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub submodules: Vec<ClangModule>,
+}
+
+/// `link` directive of a module, linking a library or framework automatically.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ModuleLink {
+    pub name: String,
+    pub framework: bool,
+}
+
+/// Public or open declaration of a Swift module, read from its `.swiftinterface`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ApiDeclaration {
+    pub kind: DeclarationKind,
+    /// Name qualified by the enclosing types, with argument labels for functions
+    /// (e.g. `Greeter.greet(name:)`).
+    pub name: String,
+    /// Declaration without its attributes and body, on a single line.
+    pub signature: String,
+    /// Kind of the type or extension the declaration is a member of.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container: Option<DeclarationKind>,
+    /// Generic parameters and `where` clause.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generic_signature: Option<String>,
+    /// `@available` attributes.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub availability: Vec<String>,
+    /// Other attributes (e.g. `@frozen`, `@inlinable`, `@objc`).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<String>,
+    /// Slices declaring it, left empty when every slice does.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub library_identifiers: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum DeclarationKind {
+    Class,
+    Struct,
+    Enum,
+    Actor,
+    Protocol,
+    Extension,
+    Func,
+    Initializer,
+    Subscript,
+    Var,
+    Let,
+    Typealias,
+    AssociatedType,
+    Case,
+    Macro,
+    Operator,
+    PrecedenceGroup,
+}
+
+/// Symbols of one architecture of a slice binary.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SymbolTable {
+    pub library_identifier: String,
+    pub architecture: String,
+    pub symbols: Vec<SymbolInfo>,
+}
+
+/// Symbol exported or referenced by a binary.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SymbolInfo {
+    /// Name as stored in the binary (e.g. `_$s13TestFramework7GreeterVMn`).
+    pub name: String,
+    /// Demangled Swift or C++ name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub demangled: Option<String>,
+    pub kind: SymbolKind,
+    /// Weak definition, or weak reference for undefined symbols.
+    pub weak: bool,
+    /// Install name of the library expected to define an undefined symbol, or re-exporting an exported one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub library: Option<String>,
+    /// Static archive member defining or referencing the symbol.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub object: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolKind {
+    /// Defined by the binary and visible to its clients.
+    Exported,
+    /// Expected from another library (or, for static archives, from the app).
+    Undefined,
+}
+
+/// Architecture found in a slice binary, read from its Mach-O header and load commands.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ArchitectureInfo {
+    pub name: String,
+    pub cpu_type: u32,
+    pub cpu_subtype: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minos: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sdk: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<BuildTool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub install_name: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rpaths: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dsym: Option<DsymInfo>,
+}
+
+/// Availability of the debug symbols of an architecture.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DsymInfo {
+    pub status: DsymStatus,
+    /// dSYM binary that matched, or the stale one when mismatched.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// UUID of the stale dSYM when mismatched.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DsymStatus {
+    Present,
+    Missing,
+    Mismatched,
+}
+
+/// Tool recorded in `LC_BUILD_VERSION` (ld, clang, swift).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BuildTool {
+    pub name: String,
+    pub version: String,
+}
+
+/// Code signature of the xcframework or of a slice, read from a `_CodeSignature`
+/// directory or from the `LC_CODE_SIGNATURE` of the binary.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SignatureInfo {
+    pub signed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub certificate_type: Option<CertificateType>,
+    /// Common name of the signing certificate (e.g. `Apple Distribution: Example Inc (ABCDE12345)`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identity: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub team_id: Option<String>,
+    /// `signingTime` attribute of the CMS signature, in UTC.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_time: Option<String>,
+    /// Identifier sealed in the CodeDirectory (usually the bundle identifier).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<String>,
+    /// Hash types of the CodeDirectories (`sha1`, `sha256`...).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub hash_types: Vec<String>,
+    /// Requirements of the signature, in `csreq` text form.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub requirements: Vec<String>,
+    /// Outcome of checking the sealed content against the signature.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verification: Option<SignatureVerification>,
+}
+
+/// Result of recomputing the hashes sealed by a signature: the CodeResources of the
+/// bundle, its special slots and the CodeDirectory page hashes of its binary.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SignatureVerification {
+    pub valid: bool,
+    /// Files, relative to the signed bundle, whose content no longer matches the seal.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tampered: Vec<String>,
+    /// Sealed files that are not in the archive.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing: Vec<String>,
+    /// Files added to the bundle after it was sealed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extra: Vec<String>,
+}
+
+/// Kind of certificate a signature was made with.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CertificateType {
+    AppleDevelopment,
+    AppleDistribution,
+    DeveloperId,
+    SelfSigned,
+    /// Signed without a certificate (`codesign --sign -`).
+    AdHoc,
+    Other,
+}
+
+/// Inconsistency detected while inspecting a framework.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Warning {
+    pub code: String,
+    pub message: String,
+    /// Path of the offending file inside the archive, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -49,6 +473,46 @@ pub struct AccessedAPIType {
     pub reasons: Vec<String>,
 }
 
+/// Whether a framework is on Apple's list of commonly used third-party SDKs, which
+/// must ship a privacy manifest and a signature, and whether it does.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SdkRequirements {
+    pub listed: bool,
+    /// Name of the SDK on the list the framework matched.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sdk_name: Option<String>,
+    /// Whether every slice has a privacy manifest.
+    pub privacy_manifest: bool,
+    /// Whether the xcframework is signed, with a signature that verifies.
+    pub signed: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub slices_missing_privacy_manifest: Vec<String>,
+    /// Whether the framework meets the requirements, or does not have to.
+    pub satisfied: bool,
+}
+
+/// Privacy manifest found in a slice, either the library's own or one of a resource bundle.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PrivacyManifest {
+    pub path: String,
+    /// Name of the resource bundle the manifest belongs to, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bundle: Option<String>,
+    pub privacy_info: PrivacyInfo,
+}
+
+/// Required-reason API category used by a slice binary or declared in its privacy manifest.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RequiredReasonApiUsage {
+    /// `NSPrivacyAccessedAPIType` value, e.g. `NSPrivacyAccessedAPICategoryUserDefaults`.
+    pub category: String,
+    pub used: bool,
+    pub declared: bool,
+    /// Symbols and selectors of the category the binary references.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub evidence: Vec<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PrivacyInfo {
     pub present: bool,
@@ -0,0 +1,26 @@
+pub mod framework_info;
+// Declaring the framework_info module.
+
+pub mod xcframework_processing;
+// Declaring the xcframework_processing module.
+
+pub mod utils;
+// Declaring the utils module.
+
+pub mod diff;
+// Declaring the diff module.
+
+pub mod api_diff;
+// Declaring the api_diff module.
+
+pub mod privacy_label;
+// Declaring the privacy_label module.
+
+pub mod privacy_report;
+// Declaring the privacy_report module.
+
+pub mod policy;
+// Declaring the policy module.
+
+pub mod sarif;
+// Declaring the SARIF module.
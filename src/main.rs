@@ -1,17 +1,32 @@
-use clap::{arg, command, Parser};
+use clap::{Parser, Subcommand};
 // Importing macros and traits from the clap crate.
 
-use xcframework_processing::collect_framework_info;
+use xcspec::xcframework_processing::{collect_framework_info, collect_symbols, CollectOptions};
 // Importing the collect_framework_info function from the xcframework_processing module.
 
-mod framework_info;
-// Declaring the framework_info module.
+use xcspec::xcframework_processing::symbols::SymbolFilter;
+use xcspec::framework_info::SymbolKind;
+// Importing the types selecting the symbols to list.
 
-mod xcframework_processing;
-// Declaring the xcframework_processing module.
+use xcspec::diff::diff_frameworks;
+// Importing the function comparing two framework reports.
 
-mod utils;
-// Declaring the utils module.
+use xcspec::api_diff::diff_api;
+// Importing the function comparing the public API of two frameworks.
+
+use xcspec::privacy_label::build_privacy_label;
+// Importing the function building the App Store privacy label of frameworks.
+
+use xcspec::privacy_report::build_privacy_report;
+use xcspec::xcframework_processing::archive_source::find_archives;
+use xcspec::xcframework_processing::privacy_manifest::privacy_manifest_plist;
+// Importing the functions aggregating the privacy manifests of many frameworks.
+
+use xcspec::policy::{check_framework, find_policy, load_policy, CheckReport, Policy};
+// Importing the policy checks.
+
+use xcspec::sarif::sarif_log;
+// Importing the conversion of findings to SARIF.
 
 use std::fs::File;
 use std::io::Write;
@@ -22,17 +37,23 @@ use std::io::Write;
     about = "A tool to extract and format information of iOS, macOS, and Apple-like framework & libraries",
     long_about = None,
     author = "Ezequiel (Kimi) Aceto <ezequiel.aceto@gmail.com>",
-    after_help = "Copyright © 2024 Ezequiel (Kimi) Aceto"
+    after_help = "Copyright © 2024 Ezequiel (Kimi) Aceto",
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
 )]
 // Defining the Args struct with the Parser and Debug traits. This struct will handle command-line arguments.
 struct Args {
-    /// path of the framework to analyse
+    #[command(subcommand)]
+    command: Option<Command>,
+    // Optional subcommand; without one, the framework given in the arguments below is inspected.
+
+    /// path of the framework to analyse (zip file or .xcframework directory)
     #[arg(short, long)]
     file: Option<String>,
     // Optional argument for the framework file path.
 
     /// export format of the report
-    #[arg(value_enum, long, short = 't', default_value_t = ExportFormat::JSON)]
+    #[arg(value_enum, long, short = 't', default_value_t = ExportFormat::Json)]
     output_format: ExportFormat,
     // Argument for the export format, defaulting to JSON.
 
@@ -45,50 +66,468 @@ struct Args {
     #[arg(short, long)]
     output: Option<String>,
     // Optional argument for the output file path.
+
+    /// directory with dSYM bundles shipped separately from the framework
+    #[arg(long)]
+    dsym_dir: Option<String>,
+    // Optional argument for a directory to look for dSYMs in, besides the framework itself.
+
+    /// include the public API of the Swift module in the report
+    #[arg(long)]
+    api: bool,
+    // Flag to add the declarations of the .swiftinterface files to the report.
+
+    /// file with the names of the commonly used third-party SDKs, one per line, replacing the built-in list
+    #[arg(long)]
+    sdk_list: Option<String>,
+    // Optional argument for an updated list of the SDKs that must ship a privacy manifest and a signature.
+}
+
+#[derive(Subcommand, Debug)]
+// Enum with the subcommands of the tool.
+enum Command {
+    /// compare two versions of a framework
+    Diff {
+        /// path of the old version of the framework
+        old: String,
+        // Positional argument for the old framework path.
+
+        /// path of the new version of the framework
+        new: String,
+        // Positional argument for the new framework path.
+
+        /// format of the diff
+        #[arg(value_enum, long, short = 't', default_value_t = ReportFormat::Json)]
+        output_format: ReportFormat,
+        // Argument for the diff format, defaulting to JSON.
+
+        /// output file path
+        #[arg(short, long)]
+        output: Option<String>,
+        // Optional argument for the output file path.
+    },
+    /// compare the public Swift API of two versions of a framework and check their version bump
+    ApiDiff {
+        /// path of the old version of the framework
+        old: String,
+        // Positional argument for the old framework path.
+
+        /// path of the new version of the framework
+        new: String,
+        // Positional argument for the new framework path.
+
+        /// format of the diff
+        #[arg(value_enum, long, short = 't', default_value_t = ReportFormat::Json)]
+        output_format: ReportFormat,
+        // Argument for the diff format, defaulting to JSON.
+
+        /// output file path
+        #[arg(short, long)]
+        output: Option<String>,
+        // Optional argument for the output file path.
+    },
+    /// list the symbols exported and referenced by the binaries of a framework
+    Symbols {
+        /// path of the framework
+        file: String,
+        // Positional argument for the framework path.
+
+        /// only list the symbols of this slice (library identifier)
+        #[arg(long)]
+        slice: Option<String>,
+        // Optional argument to select a slice.
+
+        /// only list the symbols of this architecture
+        #[arg(long)]
+        arch: Option<String>,
+        // Optional argument to select an architecture.
+
+        /// only list symbols of this kind
+        #[arg(value_enum, long)]
+        kind: Option<SymbolKindArg>,
+        // Optional argument to select exported, undefined or weak symbols.
+
+        /// only list symbols whose mangled or demangled name contains this text
+        #[arg(long)]
+        name: Option<String>,
+        // Optional argument to search for symbols.
+
+        /// format of the list
+        #[arg(value_enum, long, short = 't', default_value_t = ReportFormat::Json)]
+        output_format: ReportFormat,
+        // Argument for the list format, defaulting to JSON.
+
+        /// output file path
+        #[arg(short, long)]
+        output: Option<String>,
+        // Optional argument for the output file path.
+    },
+    /// build the App Store Connect privacy label of the data collected by one or more frameworks
+    PrivacyLabel {
+        /// paths of the frameworks
+        #[arg(required = true)]
+        files: Vec<String>,
+        // Positional arguments for the framework paths.
+
+        /// format of the label
+        #[arg(value_enum, long, short = 't', default_value_t = LabelFormat::Markdown)]
+        output_format: LabelFormat,
+        // Argument for the label format, defaulting to Markdown.
+
+        /// output file path
+        #[arg(short, long)]
+        output: Option<String>,
+        // Optional argument for the output file path.
+    },
+    /// merge the privacy manifests of many frameworks into an app-level report
+    PrivacyReport {
+        /// paths of the frameworks, or of directories to search for frameworks
+        #[arg(required = true)]
+        paths: Vec<String>,
+        // Positional arguments for the framework or directory paths.
+
+        /// write the merged PrivacyInfo.xcprivacy to this path
+        #[arg(long)]
+        manifest: Option<String>,
+        // Optional argument for the path of the merged privacy manifest.
+
+        /// format of the report
+        #[arg(value_enum, long, short = 't', default_value_t = ReportFormat::Json)]
+        output_format: ReportFormat,
+        // Argument for the report format, defaulting to JSON.
+
+        /// output file path
+        #[arg(short, long)]
+        output: Option<String>,
+        // Optional argument for the output file path.
+    },
+    /// check frameworks against a policy; exits with 1 on warnings and 2 on errors
+    Check {
+        /// paths of the frameworks
+        #[arg(required = true)]
+        files: Vec<String>,
+        // Positional arguments for the framework paths.
+
+        /// policy file (TOML or YAML), defaults to .xcspec.toml, .xcspec.yml or .xcspec.yaml in the current directory
+        #[arg(long)]
+        policy: Option<String>,
+        // Optional argument for the policy path.
+
+        /// format of the findings
+        #[arg(value_enum, long, short = 't', default_value_t = CheckFormat::Text)]
+        output_format: CheckFormat,
+        // Argument for the findings format, defaulting to text.
+
+        /// output file path
+        #[arg(short, long)]
+        output: Option<String>,
+        // Optional argument for the output file path.
+    },
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+// Enum to specify the kinds of symbols that can be listed.
+enum SymbolKindArg {
+    Exported,
+    Undefined,
+    Weak,
 }
 
 #[derive(clap::ValueEnum, Clone, Debug)]
 // Enum to specify the export format options.
 enum ExportFormat {
-    JSON,
-    YAML,
+    Json,
+    Yaml,
+    /// warnings of the report as a SARIF 2.1.0 log
+    Sarif,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+// Enum to specify the format options of the subcommands, which include a human-readable one.
+enum ReportFormat {
+    Json,
+    Yaml,
+    Text,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+// Enum to specify the format options of the findings of a policy check.
+enum CheckFormat {
+    Json,
+    Yaml,
+    Text,
+    Sarif,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+// Enum to specify the format options of the privacy label.
+enum LabelFormat {
+    Markdown,
+    Json,
 }
 
 fn main() {
     let args = Args::parse();
     // Parsing the command-line arguments into an instance of Args.
 
-    let zip_path = args.file.or(args.positional_file).expect("File path is required");
+    match args.command {
+        Some(Command::Diff { old, new, output_format, output }) => return diff(old, new, output_format, output),
+        // Comparing two frameworks instead of inspecting one.
+        Some(Command::ApiDiff { old, new, output_format, output }) => return api_diff(old, new, output_format, output),
+        // Comparing the public API of two frameworks.
+        Some(Command::Symbols { file, slice, arch, kind, name, output_format, output }) => {
+            let filter = SymbolFilter {
+                library_identifier: slice,
+                architecture: arch,
+                kind: match kind {
+                    Some(SymbolKindArg::Exported) => Some(SymbolKind::Exported),
+                    Some(SymbolKindArg::Undefined) => Some(SymbolKind::Undefined),
+                    Some(SymbolKindArg::Weak) | None => None,
+                },
+                weak: matches!(kind, Some(SymbolKindArg::Weak)),
+                name,
+            };
+            return symbols(file, &filter, output_format, output);
+        }
+        // Listing the symbols of a framework.
+        Some(Command::PrivacyLabel { files, output_format, output }) => return privacy_label(files, output_format, output),
+        // Building the privacy label of one or more frameworks.
+        Some(Command::PrivacyReport { paths, manifest, output_format, output }) => return privacy_report(paths, manifest, output_format, output),
+        // Merging the privacy manifests of many frameworks.
+        Some(Command::Check { files, policy, output_format, output }) => return check(files, policy, output_format, output),
+        // Checking frameworks against a policy.
+        None => {}
+    }
+
+    let framework_path = args.file.or(args.positional_file).expect("File path is required");
     // Getting the file path from either the file argument or the positional argument.
 
     let output_format: ExportFormat = args.output_format;
     // Getting the export format from the arguments.
 
-    match collect_framework_info(zip_path) {
+    let options = CollectOptions {
+        dsym_dir: args.dsym_dir.map(Into::into),
+        api: args.api,
+        sdk_list: args.sdk_list.map(Into::into),
+    };
+    // Gathering the optional inputs of the collection.
+
+    match collect_framework_info(&framework_path, &options) {
         // Collecting framework information.
         Ok(info) => {
             // If successful, serialize the info to the desired format and print it or write it to a file.
             let output = match output_format {
-                ExportFormat::YAML => {
+                ExportFormat::Yaml => {
                     serde_yaml::to_string(&info).expect("Failed to serialize to YAML")
                     // Serialize to YAML if specified.
                 }
-                ExportFormat::JSON => {
+                ExportFormat::Json => {
                     serde_json::to_string_pretty(&info).expect("Failed to serialize to JSON")
                     // Serialize to JSON if specified.
                 }
+                ExportFormat::Sarif => {
+                    let mut report = CheckReport::default();
+                    report.add(&framework_path, check_framework(&framework_path, &info, &Policy::default()).unwrap_or_default());
+                    serde_json::to_string_pretty(&sarif_log(&report)).expect("Failed to serialize to SARIF")
+                    // Serialize the warnings to SARIF if specified.
+                }
             };
 
-            if let Some(output_path) = args.output {
-                // If the output argument is provided, write the output to the specified file.
-                let mut file = File::create(output_path).expect("Failed to create output file");
-                file.write_all(output.as_bytes()).expect("Failed to write to output file");
-            } else {
-                // Otherwise, print the output to the console.
-                println!("{}", output);
+            write_output(&output, args.output);
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        // Print the error and fail if the information collection fails.
+    }
+}
+
+fn diff(old: String, new: String, output_format: ReportFormat, output_path: Option<String>) {
+    let options = CollectOptions::default();
+    // The diff compares the default reports of both frameworks.
+
+    let (old_info, new_info) = match (collect_framework_info(&old, &options), collect_framework_info(&new, &options)) {
+        (Ok(old_info), Ok(new_info)) => (old_info, new_info),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        // Print the error and fail if the information collection of either framework fails.
+    };
+
+    let diff = diff_frameworks(&old_info, &new_info);
+    let output = match output_format {
+        ReportFormat::Yaml => serde_yaml::to_string(&diff).expect("Failed to serialize to YAML"),
+        ReportFormat::Json => serde_json::to_string_pretty(&diff).expect("Failed to serialize to JSON"),
+        ReportFormat::Text => diff.to_string().trim_end().to_string(),
+    };
+    // Serializing the diff to the desired format.
+
+    write_output(&output, output_path);
+}
+
+fn api_diff(old: String, new: String, output_format: ReportFormat, output_path: Option<String>) {
+    let options = CollectOptions { api: true, ..Default::default() };
+    // The API inventory is only collected on demand.
+
+    let (old_info, new_info) = match (collect_framework_info(&old, &options), collect_framework_info(&new, &options)) {
+        (Ok(old_info), Ok(new_info)) => (old_info, new_info),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        // The verdict cannot be given if either framework cannot be read.
+    };
+
+    let diff = diff_api(&old_info, &new_info);
+    let output = match output_format {
+        ReportFormat::Yaml => serde_yaml::to_string(&diff).expect("Failed to serialize to YAML"),
+        ReportFormat::Json => serde_json::to_string_pretty(&diff).expect("Failed to serialize to JSON"),
+        ReportFormat::Text => diff.to_string().trim_end().to_string(),
+    };
+    // Serializing the diff to the desired format.
+
+    write_output(&output, output_path);
+    if diff.is_unjustified_break() {
+        std::process::exit(1);
+        // Failing when the API breaks without the matching version bump.
+    }
+}
+
+fn symbols(file: String, filter: &SymbolFilter, output_format: ReportFormat, output_path: Option<String>) {
+    let tables = match collect_symbols(&file, filter) {
+        Ok(tables) => tables,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        // Print the error and fail if the symbols cannot be read.
+    };
+
+    let output = match output_format {
+        ReportFormat::Yaml => serde_yaml::to_string(&tables).expect("Failed to serialize to YAML"),
+        ReportFormat::Json => serde_json::to_string_pretty(&tables).expect("Failed to serialize to JSON"),
+        ReportFormat::Text => tables.iter().map(ToString::to_string).collect::<Vec<String>>().join("\n").trim_end().to_string(),
+    };
+    // Serializing the symbols to the desired format.
+
+    write_output(&output, output_path);
+}
+
+fn privacy_label(files: Vec<String>, output_format: LabelFormat, output_path: Option<String>) {
+    let options = CollectOptions::default();
+    let mut frameworks = Vec::new();
+    for file in &files {
+        match collect_framework_info(file, &options) {
+            Ok(info) => frameworks.push(info),
+            Err(e) => {
+                eprintln!("Error: {}: {}", file, e);
+                std::process::exit(1);
             }
+            // Print the error and fail if the information collection of any framework fails.
         }
-        Err(e) => eprintln!("Error: {}", e),
-        // Print the error if the information collection fails.
+    }
+
+    let label = build_privacy_label(&frameworks);
+    let output = match output_format {
+        LabelFormat::Json => serde_json::to_string_pretty(&label).expect("Failed to serialize to JSON"),
+        LabelFormat::Markdown => label.to_string().trim_end().to_string(),
+    };
+    // Serializing the label to the desired format.
+
+    write_output(&output, output_path);
+}
+
+fn privacy_report(paths: Vec<String>, manifest_path: Option<String>, output_format: ReportFormat, output_path: Option<String>) {
+    let mut archives = Vec::new();
+    for path in &paths {
+        match find_archives(path) {
+            Ok(found) => archives.extend(found),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            // Print the error and fail if a directory cannot be searched.
+        }
+    }
+
+    let options = CollectOptions::default();
+    let mut frameworks = Vec::new();
+    for archive in archives {
+        let path = archive.display().to_string();
+        match collect_framework_info(&archive, &options) {
+            Ok(info) => frameworks.push((path, info)),
+            Err(e) => eprintln!("Warning: skipping {}: {}", path, e),
+            // Files that are not frameworks do not stop the aggregation.
+        }
+    }
+
+    let report = build_privacy_report(&frameworks);
+    if let Some(manifest_path) = manifest_path {
+        if let Err(e) = privacy_manifest_plist(&report.privacy_info).to_file_xml(&manifest_path) {
+            eprintln!("Error: Failed to write the privacy manifest {}: {}", manifest_path, e);
+            std::process::exit(1);
+        }
+        // Writing the merged privacy manifest to embed in the app, failing if it cannot be written.
+    }
+
+    let output = match output_format {
+        ReportFormat::Yaml => serde_yaml::to_string(&report).expect("Failed to serialize to YAML"),
+        ReportFormat::Json => serde_json::to_string_pretty(&report).expect("Failed to serialize to JSON"),
+        ReportFormat::Text => report.to_string().trim_end().to_string(),
+    };
+    // Serializing the report to the desired format.
+
+    write_output(&output, output_path);
+}
+
+fn check(files: Vec<String>, policy_path: Option<String>, output_format: CheckFormat, output_path: Option<String>) {
+    let policy_path = policy_path.map(Into::into).or_else(|| std::env::current_dir().ok().and_then(|directory| find_policy(&directory)));
+    let policy = match policy_path.map(|path| load_policy(&path)) {
+        Some(Ok(policy)) => policy,
+        Some(Err(e)) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(2);
+        }
+        None => Policy::default(),
+        // Without a policy, only the warnings of the report are findings.
+    };
+
+    let options = CollectOptions::default();
+    let mut report = CheckReport::default();
+    for file in &files {
+        let findings = collect_framework_info(file, &options).and_then(|info| check_framework(file, &info, &policy));
+        match findings {
+            Ok(findings) => report.add(file, findings),
+            Err(e) => {
+                eprintln!("Error: {}: {}", file, e);
+                std::process::exit(2);
+            }
+            // A framework that cannot be checked fails like an error finding.
+        }
+    }
+
+    let output = match output_format {
+        CheckFormat::Yaml => serde_yaml::to_string(&report).expect("Failed to serialize to YAML"),
+        CheckFormat::Json => serde_json::to_string_pretty(&report).expect("Failed to serialize to JSON"),
+        CheckFormat::Text => report.to_string().trim_end().to_string(),
+        CheckFormat::Sarif => serde_json::to_string_pretty(&sarif_log(&report)).expect("Failed to serialize to SARIF"),
+    };
+    // Serializing the findings to the desired format.
+
+    write_output(&output, output_path);
+    std::process::exit(report.exit_code());
+    // Exiting with the worst severity of the findings.
+}
+
+fn write_output(output: &str, output_path: Option<String>) {
+    if let Some(output_path) = output_path {
+        // If the output argument is provided, write the output to the specified file.
+        let mut file = File::create(output_path).expect("Failed to create output file");
+        file.write_all(output.as_bytes()).expect("Failed to write to output file");
+    } else {
+        // Otherwise, print the output to the console.
+        println!("{}", output);
     }
 }
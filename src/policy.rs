@@ -0,0 +1,430 @@
+use std::collections::BTreeMap;
+use std::cmp::Ordering;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::framework_info::FrameworkInfo;
+use crate::utils::{compare_versions, format_size, parse_size};
+
+/// File names a policy is looked up with, in order, when none is given.
+pub const POLICY_FILE_NAMES: &[&str] = &[".xcspec.toml", ".xcspec.yml", ".xcspec.yaml"];
+
+/// Rules a framework must follow, read from a `.xcspec.toml` or `.xcspec.yml` file.
+/// Platforms are written with their variant, e.g. `ios`, `ios-simulator` or `ios-maccatalyst`.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Policy {
+    /// Platforms the framework must have a slice for.
+    pub required_platforms: Vec<String>,
+    /// Architectures every slice of a platform must contain, by platform.
+    pub required_architectures: BTreeMap<String, Vec<String>>,
+    /// Highest minimum OS version allowed, by platform.
+    pub maximum_minimum_os: BTreeMap<String, String>,
+    pub require_library_evolution: bool,
+    /// Every slice must ship a privacy manifest.
+    pub require_privacy_manifest: bool,
+    /// Swift imports and linked libraries (install name or file name) that must not be used.
+    pub forbidden_dependencies: Vec<String>,
+    /// Largest binary allowed in a slice, in bytes or with a unit (e.g. `"50 MB"`).
+    pub max_slice_size: Option<SizeLimit>,
+    pub require_signature: bool,
+    /// Team IDs the framework may be signed by.
+    pub team_ids: Vec<String>,
+    /// Severity of rules and warning codes, overriding their default.
+    pub severity: BTreeMap<String, Severity>,
+    /// Rules and warning codes that do not produce findings.
+    pub ignore: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum SizeLimit {
+    Bytes(u64),
+    Text(String),
+}
+
+/// Severity of a finding; the worst one decides the exit code of `xcspec check`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Note,
+    Warning,
+    Error,
+}
+
+/// A rule of the policy, or a warning of the report, that a framework does not follow.
+#[derive(Serialize, Debug, Clone)]
+pub struct Finding {
+    /// Policy rule (e.g. `required-platform`) or warning code (e.g. `debug-build`).
+    pub rule: String,
+    pub severity: Severity,
+    pub message: String,
+    /// Path of the framework the finding is about.
+    pub framework: String,
+    /// Path of the offending file inside the framework, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+/// Findings of `xcspec check` over one or more frameworks.
+#[derive(Serialize, Debug, Default)]
+pub struct CheckReport {
+    pub frameworks: Vec<String>,
+    /// Worst severity of the findings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
+    pub findings: Vec<Finding>,
+}
+
+impl CheckReport {
+    pub fn add(&mut self, framework_path: &str, findings: Vec<Finding>) {
+        self.frameworks.push(framework_path.to_string());
+        self.findings.extend(findings);
+        self.severity = self.findings.iter().map(|finding| finding.severity).max();
+    }
+
+    /// Exit code of `xcspec check`: 0 without findings above notes, 1 for warnings, 2 for errors.
+    pub fn exit_code(&self) -> i32 {
+        match self.severity {
+            Some(Severity::Error) => 2,
+            Some(Severity::Warning) => 1,
+            Some(Severity::Note) | None => 0,
+        }
+    }
+}
+
+/// Reads a policy, as YAML for `.yml` and `.yaml` files and as TOML otherwise.
+pub fn load_policy(path: &Path) -> Result<Policy, String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| format!("Failed to read the policy: {}", e))?;
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("yml" | "yaml") => serde_yaml::from_str(&contents).map_err(|e| format!("Failed to parse the policy: {}", e)),
+        _ => toml::from_str(&contents).map_err(|e| format!("Failed to parse the policy: {}", e)),
+    }
+}
+
+/// Finds the policy file of a directory, if any.
+pub fn find_policy(directory: &Path) -> Option<PathBuf> {
+    POLICY_FILE_NAMES.iter().map(|name| directory.join(name)).find(|path| path.is_file())
+}
+
+/// Checks a framework against a policy. The warnings of the report are findings too,
+/// with the `warning` severity unless the policy overrides it.
+pub fn check_framework(framework_path: &str, info: &FrameworkInfo, policy: &Policy) -> Result<Vec<Finding>, String> {
+    let mut checker = Checker {
+        framework_path,
+        policy,
+        findings: Vec::new(),
+    };
+    let libraries = info.available_libraries.as_deref().unwrap_or_default();
+
+    for platform in &policy.required_platforms {
+        if !libraries.iter().any(|library| &library.platform_name() == platform) {
+            checker.report("required-platform", Severity::Error, format!("No slice for the required platform {}", platform), None);
+        }
+    }
+
+    let max_slice_size = match &policy.max_slice_size {
+        Some(SizeLimit::Bytes(bytes)) => Some(*bytes),
+        Some(SizeLimit::Text(text)) => Some(parse_size(text).ok_or_else(|| format!("Invalid max_slice_size '{}'", text))?),
+        None => None,
+    };
+
+    for library in libraries {
+        let platform = library.platform_name();
+        let slice = &library.library_identifier;
+
+        for architecture in policy.required_architectures.get(&platform).into_iter().flatten() {
+            if !library.supported_architectures.contains(architecture) {
+                checker.report(
+                    "required-architecture",
+                    Severity::Error,
+                    format!("Slice {} does not contain the required architecture {}", slice, architecture),
+                    None,
+                );
+            }
+        }
+
+        if let (Some(maximum), Some(minimum_os)) = (policy.maximum_minimum_os.get(&platform), &library.minimum_os_version) {
+            if compare_versions(minimum_os, maximum) == Ordering::Greater {
+                checker.report(
+                    "maximum-minimum-os",
+                    Severity::Error,
+                    format!("Slice {} requires {} {}, above the allowed {}", slice, platform, minimum_os, maximum),
+                    None,
+                );
+            }
+        }
+
+        if policy.require_privacy_manifest && !library.privacy_info.as_ref().is_some_and(|privacy_info| privacy_info.present) {
+            checker.report(
+                "privacy-manifest",
+                Severity::Error,
+                format!("Slice {} has no privacy manifest", slice),
+                None,
+            );
+        }
+
+        let linked = library.linked_libraries.iter().map(|linked| linked.install_name.as_str());
+        let dependencies: Vec<&str> = library.dependencies.iter().map(String::as_str).chain(linked).collect();
+        for forbidden in &policy.forbidden_dependencies {
+            let used = dependencies
+                .iter()
+                .any(|dependency| dependency == forbidden || dependency.rsplit('/').next() == Some(forbidden.as_str()));
+            if used {
+                checker.report(
+                    "forbidden-dependency",
+                    Severity::Error,
+                    format!("Slice {} depends on the forbidden {}", slice, forbidden),
+                    None,
+                );
+            }
+        }
+
+        if let (Some(maximum), Some(size)) = (max_slice_size, library.size_bytes) {
+            if size > maximum {
+                checker.report(
+                    "max-slice-size",
+                    Severity::Error,
+                    format!("Slice {} is {}, above the allowed {}", slice, format_size(size), format_size(maximum)),
+                    None,
+                );
+            }
+        }
+    }
+
+    if policy.require_library_evolution && !info.library_evolution_enabled {
+        checker.report(
+            "library-evolution",
+            Severity::Error,
+            "The framework is not built with library evolution (BUILD_LIBRARY_FOR_DISTRIBUTION)".to_string(),
+            None,
+        );
+    }
+
+    let signature = info.signature.as_ref().filter(|signature| signature.signed);
+    if policy.require_signature {
+        match signature.and_then(|signature| signature.verification.as_ref()) {
+            _ if signature.is_none() => {
+                checker.report("signature", Severity::Error, "The framework is not signed".to_string(), None)
+            }
+            Some(verification) if !verification.valid => {
+                checker.report("signature", Severity::Error, "The signature of the framework is not valid".to_string(), None)
+            }
+            _ => {}
+        }
+    }
+    if !policy.team_ids.is_empty() {
+        let team_id = signature.and_then(|signature| signature.team_id.as_deref());
+        if !team_id.is_some_and(|team_id| policy.team_ids.iter().any(|allowed| allowed == team_id)) {
+            checker.report(
+                "team-id",
+                Severity::Error,
+                format!(
+                    "The framework is signed by team {}, not one of {}",
+                    team_id.unwrap_or("none"),
+                    policy.team_ids.join(", ")
+                ),
+                None,
+            );
+        }
+    }
+
+    let warnings = info.warnings.iter().chain(libraries.iter().flat_map(|library| &library.warnings));
+    for warning in warnings {
+        checker.report(&warning.code, Severity::Warning, warning.message.clone(), warning.path.clone());
+    }
+    Ok(checker.findings)
+}
+
+struct Checker<'a> {
+    framework_path: &'a str,
+    policy: &'a Policy,
+    findings: Vec<Finding>,
+}
+
+impl Checker<'_> {
+    /// Adds a finding, unless the policy ignores its rule, with the severity the policy gives it.
+    fn report(&mut self, rule: &str, severity: Severity, message: String, path: Option<String>) {
+        if self.policy.ignore.iter().any(|ignored| ignored == rule) {
+            return;
+        }
+        self.findings.push(Finding {
+            rule: rule.to_string(),
+            severity: self.policy.severity.get(rule).copied().unwrap_or(severity),
+            message,
+            framework: self.framework_path.to_string(),
+            path,
+        });
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Note => "note",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+impl fmt::Display for CheckReport {
+    /// One line per finding, as `severity [rule] framework: message`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for finding in &self.findings {
+            write!(f, "{} [{}] {}: {}", finding.severity, finding.rule, finding.framework, finding.message)?;
+            if let Some(path) = &finding.path {
+                write!(f, " ({})", path)?;
+            }
+            writeln!(f)?;
+        }
+        let count = |severity| self.findings.iter().filter(|finding| finding.severity == severity).count();
+        writeln!(
+            f,
+            "{} error(s), {} warning(s), {} note(s) in {} framework(s)",
+            count(Severity::Error),
+            count(Severity::Warning),
+            count(Severity::Note),
+            self.frameworks.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework_info::{LibraryInfo, PrivacyInfo, SignatureInfo, SignatureVerification, Warning};
+
+    /// Unsigned framework without library evolution: a device slice of 60 MB depending on
+    /// AdSupport without a privacy manifest, and a simulator slice with one.
+    fn framework() -> FrameworkInfo {
+        let privacy_info = PrivacyInfo {
+            present: true,
+            tracking: None,
+            tracking_domains: None,
+            collected_data_types: None,
+            accessed_api_types: None,
+        };
+        FrameworkInfo {
+            available_libraries: Some(vec![
+                LibraryInfo {
+                    library_identifier: "ios-arm64".to_string(),
+                    supported_platform: "ios".to_string(),
+                    supported_architectures: vec!["arm64".to_string()],
+                    minimum_os_version: Some("15.0".to_string()),
+                    size_bytes: Some(60_000_000),
+                    dependencies: vec!["AdSupport".to_string()],
+                    ..Default::default()
+                },
+                LibraryInfo {
+                    library_identifier: "ios-arm64_x86_64-simulator".to_string(),
+                    supported_platform: "ios".to_string(),
+                    supported_platform_variant: Some("simulator".to_string()),
+                    supported_architectures: vec!["arm64".to_string(), "x86_64".to_string()],
+                    minimum_os_version: Some("13.0".to_string()),
+                    size_bytes: Some(1_000_000),
+                    privacy_info: Some(privacy_info),
+                    warnings: vec![Warning {
+                        code: "debug-build".to_string(),
+                        message: "built without optimizations".to_string(),
+                        path: Some("Foo.xcframework/ios-arm64_x86_64-simulator/Foo.framework".to_string()),
+                    }],
+                    ..Default::default()
+                },
+            ]),
+            ..Default::default()
+        }
+    }
+
+    fn policy(toml: &str) -> Policy {
+        toml::from_str(toml).expect("valid policy")
+    }
+
+    fn rules(policy: &Policy) -> Vec<(String, Severity)> {
+        check_framework("Foo.xcframework", &framework(), policy)
+            .expect("valid policy")
+            .into_iter()
+            .map(|finding| (finding.rule, finding.severity))
+            .collect()
+    }
+
+    fn errors(toml: &str) -> Vec<String> {
+        rules(&policy(toml)).into_iter().filter(|(_, severity)| *severity == Severity::Error).map(|(rule, _)| rule).collect()
+    }
+
+    #[test]
+    fn reports_warnings_of_the_report() {
+        assert_eq!(rules(&Policy::default()), [("debug-build".to_string(), Severity::Warning)]);
+    }
+
+    #[test]
+    fn checks_platforms_architectures_and_minimum_os() {
+        assert!(errors(r#"required_platforms = ["ios", "ios-simulator"]"#).is_empty());
+        assert_eq!(errors(r#"required_platforms = ["ios", "ios-maccatalyst", "tvos"]"#), ["required-platform", "required-platform"]);
+        assert_eq!(errors("[required_architectures]\nios-simulator = [\"arm64\", \"x86_64\"]\nios = [\"arm64e\"]"), ["required-architecture"]);
+        assert_eq!(errors("[maximum_minimum_os]\nios = \"14.0\"\nios-simulator = \"14.0\""), ["maximum-minimum-os"]);
+        assert!(errors("[maximum_minimum_os]\nios = \"15\"").is_empty());
+    }
+
+    #[test]
+    fn checks_privacy_dependencies_and_size() {
+        assert_eq!(errors("require_privacy_manifest = true"), ["privacy-manifest"]);
+        assert_eq!(errors(r#"forbidden_dependencies = ["AdSupport", "AppTrackingTransparency"]"#), ["forbidden-dependency"]);
+        assert_eq!(errors(r#"max_slice_size = "50 MB""#), ["max-slice-size"]);
+        assert!(errors(r#"max_slice_size = "0.1 GB""#).is_empty());
+        assert_eq!(errors("max_slice_size = 50000000"), ["max-slice-size"]);
+        let invalid = check_framework("Foo.xcframework", &framework(), &policy(r#"max_slice_size = "50 MiB""#));
+        assert_eq!(invalid.unwrap_err(), "Invalid max_slice_size '50 MiB'");
+    }
+
+    #[test]
+    fn checks_library_evolution_and_signature() {
+        assert_eq!(errors("require_library_evolution = true"), ["library-evolution"]);
+        assert_eq!(errors("require_signature = true"), ["signature"]);
+        assert_eq!(errors(r#"team_ids = ["ABCDE12345"]"#), ["team-id"]);
+
+        let mut info = framework();
+        info.library_evolution_enabled = true;
+        info.signature = Some(SignatureInfo {
+            signed: true,
+            team_id: Some("ABCDE12345".to_string()),
+            verification: Some(SignatureVerification { valid: false, ..Default::default() }),
+            ..Default::default()
+        });
+        let policy = policy("require_library_evolution = true\nrequire_signature = true\nteam_ids = [\"ABCDE12345\"]");
+        let findings = check_framework("Foo.xcframework", &info, &policy).unwrap();
+        let rules: Vec<&str> = findings.iter().map(|finding| finding.rule.as_str()).collect();
+        assert_eq!(rules, ["signature", "debug-build"]);
+        assert_eq!(findings[0].message, "The signature of the framework is not valid");
+    }
+
+    #[test]
+    fn overrides_severities_and_ignores_rules() {
+        let overridden = policy("require_privacy_manifest = true\n[severity]\nprivacy-manifest = \"warning\"\ndebug-build = \"error\"");
+        assert_eq!(
+            rules(&overridden),
+            [("privacy-manifest".to_string(), Severity::Warning), ("debug-build".to_string(), Severity::Error)]
+        );
+        let ignored = policy("require_privacy_manifest = true\nignore = [\"privacy-manifest\", \"debug-build\"]");
+        assert!(rules(&ignored).is_empty());
+    }
+
+    #[test]
+    fn maps_the_worst_severity_to_the_exit_code() {
+        let mut report = CheckReport::default();
+        assert_eq!(report.exit_code(), 0);
+        let finding = |severity| Finding {
+            rule: "debug-build".to_string(),
+            severity,
+            message: String::new(),
+            framework: "Foo.xcframework".to_string(),
+            path: None,
+        };
+        report.add("Foo.xcframework", vec![finding(Severity::Note)]);
+        assert_eq!(report.exit_code(), 0);
+        report.add("Bar.xcframework", vec![finding(Severity::Warning)]);
+        assert_eq!(report.exit_code(), 1);
+        report.add("Baz.xcframework", vec![finding(Severity::Error), finding(Severity::Note)]);
+        assert_eq!((report.severity, report.exit_code()), (Some(Severity::Error), 2));
+    }
+}
@@ -0,0 +1,210 @@
+use std::fmt;
+use serde::Serialize;
+use crate::framework_info::FrameworkInfo;
+use crate::xcframework_processing::privacy_manifest::{DATA_TYPES, PURPOSES};
+
+/// App Store Connect privacy label ("nutrition label") of the data collected by one or more frameworks.
+#[derive(Serialize, Debug, Default)]
+pub struct PrivacyLabel {
+    /// Frameworks the label was built from.
+    pub frameworks: Vec<String>,
+    /// Categories of the data used for tracking.
+    pub used_to_track_you: Vec<LabelCategory>,
+    /// Data linked to the user's identity, by purpose.
+    pub linked_to_you: Vec<LabelPurpose>,
+    /// Data not linked to the user's identity, by purpose.
+    pub not_linked_to_you: Vec<LabelPurpose>,
+    /// Answers to the App Store Connect questionnaire, one per data type.
+    pub data_types: Vec<LabelDataType>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LabelPurpose {
+    /// App Store Connect name of the purpose, e.g. `Analytics`.
+    pub purpose: String,
+    pub categories: Vec<LabelCategory>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LabelCategory {
+    /// App Store Connect name of the category, e.g. `Identifiers`.
+    pub category: String,
+    /// App Store Connect names of the data types, e.g. `User ID`.
+    pub data_types: Vec<String>,
+}
+
+/// A data type collected by at least one framework, merged across all of them.
+#[derive(Serialize, Debug, Clone)]
+pub struct LabelDataType {
+    pub category: String,
+    pub data_type: String,
+    /// `NSPrivacyCollectedDataType` value of the privacy manifests.
+    pub key: String,
+    pub linked_to_user: bool,
+    pub tracking: bool,
+    pub purposes: Vec<String>,
+    /// Frameworks collecting the data type.
+    pub frameworks: Vec<String>,
+}
+
+/// Builds the privacy label of the data types declared by the merged privacy manifests of
+/// every slice. A data type is linked or used for tracking if any framework says so.
+pub fn build_privacy_label(frameworks: &[FrameworkInfo]) -> PrivacyLabel {
+    let mut label = PrivacyLabel::default();
+    // Data types with the table position of their key, for Apple's ordering.
+    let mut collected: Vec<(usize, LabelDataType)> = Vec::new();
+
+    for framework in frameworks {
+        let name = framework.framework_name.clone().unwrap_or_else(|| "unknown".to_string());
+        if !label.frameworks.contains(&name) {
+            label.frameworks.push(name.clone());
+        }
+        let data_types = framework
+            .available_libraries
+            .iter()
+            .flatten()
+            .filter_map(|library| library.privacy_info.as_ref())
+            .filter_map(|privacy_info| privacy_info.collected_data_types.as_ref())
+            .flatten();
+        for data_type in data_types {
+            let position = collected.iter().position(|(_, collected)| collected.key == data_type.data_type);
+            let (_, entry) = match position {
+                Some(position) => &mut collected[position],
+                None => {
+                    let (order, category, label_name) = DATA_TYPES
+                        .iter()
+                        .enumerate()
+                        .find(|(_, (key, ..))| *key == data_type.data_type)
+                        .map(|(order, (_, category, name))| (order, category.to_string(), name.to_string()))
+                        .unwrap_or_else(|| (DATA_TYPES.len(), "Other Data".to_string(), data_type.data_type.clone()));
+                    collected.push((
+                        order,
+                        LabelDataType {
+                            category,
+                            data_type: label_name,
+                            key: data_type.data_type.clone(),
+                            linked_to_user: false,
+                            tracking: false,
+                            purposes: Vec::new(),
+                            frameworks: Vec::new(),
+                        },
+                    ));
+                    collected.last_mut().expect("data type was just added")
+                }
+            };
+            entry.linked_to_user |= data_type.linked_to_user;
+            entry.tracking |= data_type.tracking;
+            if !entry.frameworks.contains(&name) {
+                entry.frameworks.push(name.clone());
+            }
+            for purpose in data_type.purposes.iter().filter(|purpose| !purpose.is_empty()) {
+                let name = PURPOSES
+                    .iter()
+                    .find(|(key, _)| key == purpose)
+                    .map(|(_, name)| name.to_string())
+                    .unwrap_or_else(|| purpose.clone());
+                if !entry.purposes.contains(&name) {
+                    entry.purposes.push(name);
+                }
+            }
+        }
+    }
+
+    collected.sort_by_key(|(order, data_type)| (*order, data_type.key.clone()));
+    label.data_types = collected.into_iter().map(|(_, data_type)| data_type).collect();
+    for data_type in &mut label.data_types {
+        data_type.purposes.sort_by_key(purpose_order);
+    }
+
+    label.used_to_track_you = group_by_category(label.data_types.iter().filter(|data_type| data_type.tracking));
+    label.linked_to_you = group_by_purpose(label.data_types.iter().filter(|data_type| data_type.linked_to_user));
+    label.not_linked_to_you = group_by_purpose(label.data_types.iter().filter(|data_type| !data_type.linked_to_user));
+    label
+}
+
+fn group_by_category<'a>(data_types: impl Iterator<Item = &'a LabelDataType>) -> Vec<LabelCategory> {
+    let mut categories: Vec<LabelCategory> = Vec::new();
+    for data_type in data_types {
+        match categories.iter_mut().find(|category| category.category == data_type.category) {
+            Some(category) => category.data_types.push(data_type.data_type.clone()),
+            None => categories.push(LabelCategory {
+                category: data_type.category.clone(),
+                data_types: vec![data_type.data_type.clone()],
+            }),
+        }
+    }
+    categories
+}
+
+fn group_by_purpose<'a>(data_types: impl Iterator<Item = &'a LabelDataType> + Clone) -> Vec<LabelPurpose> {
+    let mut purposes: Vec<String> = Vec::new();
+    for data_type in data_types.clone() {
+        for purpose in &data_type.purposes {
+            if !purposes.contains(purpose) {
+                purposes.push(purpose.clone());
+            }
+        }
+    }
+    purposes.sort_by_key(purpose_order);
+    purposes
+        .into_iter()
+        .map(|purpose| LabelPurpose {
+            categories: group_by_category(data_types.clone().filter(|data_type| data_type.purposes.contains(&purpose))),
+            purpose,
+        })
+        .collect()
+}
+
+/// Position of a purpose in App Store Connect; unknown purposes come last.
+fn purpose_order(purpose: &String) -> usize {
+    PURPOSES.iter().position(|(_, name)| name == purpose).unwrap_or(PURPOSES.len())
+}
+
+impl fmt::Display for PrivacyLabel {
+    /// Markdown form of the label.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# App Privacy\n")?;
+        writeln!(f, "Frameworks: {}", self.frameworks.join(", "))?;
+
+        writeln!(f, "\n## Data Used to Track You\n")?;
+        if self.used_to_track_you.is_empty() {
+            writeln!(f, "None.")?;
+        }
+        for category in &self.used_to_track_you {
+            writeln!(f, "- **{}**: {}", category.category, category.data_types.join(", "))?;
+        }
+
+        for (title, purposes) in [("Data Linked to You", &self.linked_to_you), ("Data Not Linked to You", &self.not_linked_to_you)] {
+            writeln!(f, "\n## {}", title)?;
+            if purposes.is_empty() {
+                writeln!(f, "\nNone.")?;
+            }
+            for purpose in purposes {
+                writeln!(f, "\n### {}\n", purpose.purpose)?;
+                for category in &purpose.categories {
+                    writeln!(f, "- **{}**: {}", category.category, category.data_types.join(", "))?;
+                }
+            }
+        }
+
+        if !self.data_types.is_empty() {
+            writeln!(f, "\n## Data Types\n")?;
+            writeln!(f, "| Category | Data type | Linked to you | Used to track you | Purposes | Frameworks |")?;
+            writeln!(f, "| --- | --- | --- | --- | --- | --- |")?;
+            let yes_no = |value: bool| if value { "Yes" } else { "No" };
+            for data_type in &self.data_types {
+                writeln!(
+                    f,
+                    "| {} | {} | {} | {} | {} | {} |",
+                    data_type.category,
+                    data_type.data_type,
+                    yes_no(data_type.linked_to_user),
+                    yes_no(data_type.tracking),
+                    data_type.purposes.join(", "),
+                    data_type.frameworks.join(", ")
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
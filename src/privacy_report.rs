@@ -0,0 +1,201 @@
+use std::fmt;
+use serde::Serialize;
+use crate::framework_info::{FrameworkInfo, PrivacyInfo};
+use crate::xcframework_processing::privacy_manifest::merge_privacy_info;
+
+/// App-level view of the privacy manifests of a set of SDKs.
+#[derive(Serialize, Debug)]
+pub struct PrivacyReport {
+    pub sdks: Vec<SdkPrivacy>,
+    /// Whether any SDK declares tracking.
+    pub tracking: bool,
+    pub tracking_domains: Vec<TrackingDomain>,
+    pub collected_data_types: Vec<ReportDataType>,
+    pub accessed_api_types: Vec<ReportApiType>,
+    /// SDKs without any privacy manifest.
+    pub missing_manifest: Vec<String>,
+    /// Manifest merged from every SDK, to be embedded in the app.
+    #[serde(skip)]
+    pub privacy_info: PrivacyInfo,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SdkPrivacy {
+    pub name: String,
+    pub path: String,
+    pub has_manifest: bool,
+    pub tracking: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TrackingDomain {
+    pub domain: String,
+    /// SDKs declaring the domain.
+    pub sdks: Vec<String>,
+}
+
+/// A data type collected by at least one SDK; linked or tracking if any SDK says so.
+#[derive(Serialize, Debug, Clone)]
+pub struct ReportDataType {
+    pub data_type: String,
+    pub linked_to_user: bool,
+    pub tracking: bool,
+    pub purposes: Vec<String>,
+    pub sdks: Vec<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ReportApiType {
+    pub api: String,
+    /// Reasons declared by any SDK.
+    pub reasons: Vec<String>,
+    pub sdks: Vec<SdkReasons>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SdkReasons {
+    pub sdk: String,
+    pub reasons: Vec<String>,
+}
+
+/// Aggregates the effective privacy manifests of SDKs, given with the path they were read from.
+/// An SDK is named after its framework, or its path when the Info.plist does not name it.
+pub fn build_privacy_report(frameworks: &[(String, FrameworkInfo)]) -> PrivacyReport {
+    let mut report = PrivacyReport {
+        sdks: Vec::new(),
+        tracking: false,
+        tracking_domains: Vec::new(),
+        collected_data_types: Vec::new(),
+        accessed_api_types: Vec::new(),
+        missing_manifest: Vec::new(),
+        privacy_info: merge_privacy_info([]),
+    };
+    let mut sdk_infos = Vec::new();
+
+    for (path, framework) in frameworks {
+        let name = framework.framework_name.clone().unwrap_or_else(|| path.clone());
+        let info = merge_privacy_info(
+            framework
+                .available_libraries
+                .iter()
+                .flatten()
+                .filter_map(|library| library.privacy_info.as_ref()),
+        );
+        report.sdks.push(SdkPrivacy {
+            name: name.clone(),
+            path: path.clone(),
+            has_manifest: info.present,
+            tracking: info.tracking == Some(true),
+        });
+        if !info.present {
+            report.missing_manifest.push(name.clone());
+        }
+
+        for domain in info.tracking_domains.iter().flatten() {
+            match report.tracking_domains.iter_mut().find(|entry| &entry.domain == domain) {
+                Some(entry) => add_unique(&mut entry.sdks, &name),
+                None => report.tracking_domains.push(TrackingDomain {
+                    domain: domain.clone(),
+                    sdks: vec![name.clone()],
+                }),
+            }
+        }
+
+        for data_type in info.collected_data_types.iter().flatten() {
+            match report.collected_data_types.iter_mut().find(|entry| entry.data_type == data_type.data_type) {
+                Some(entry) => {
+                    entry.linked_to_user |= data_type.linked_to_user;
+                    entry.tracking |= data_type.tracking;
+                    data_type.purposes.iter().for_each(|purpose| add_unique(&mut entry.purposes, purpose));
+                    add_unique(&mut entry.sdks, &name);
+                }
+                None => report.collected_data_types.push(ReportDataType {
+                    data_type: data_type.data_type.clone(),
+                    linked_to_user: data_type.linked_to_user,
+                    tracking: data_type.tracking,
+                    purposes: data_type.purposes.clone(),
+                    sdks: vec![name.clone()],
+                }),
+            }
+        }
+
+        for api_type in info.accessed_api_types.iter().flatten() {
+            let reasons = SdkReasons {
+                sdk: name.clone(),
+                reasons: api_type.reasons.clone(),
+            };
+            match report.accessed_api_types.iter_mut().find(|entry| entry.api == api_type.api) {
+                Some(entry) => {
+                    api_type.reasons.iter().for_each(|reason| add_unique(&mut entry.reasons, reason));
+                    entry.sdks.push(reasons);
+                }
+                None => report.accessed_api_types.push(ReportApiType {
+                    api: api_type.api.clone(),
+                    reasons: api_type.reasons.clone(),
+                    sdks: vec![reasons],
+                }),
+            }
+        }
+        sdk_infos.push(info);
+    }
+
+    report.privacy_info = merge_privacy_info(&sdk_infos);
+    report.tracking = report.privacy_info.tracking == Some(true);
+    report
+}
+
+fn add_unique(values: &mut Vec<String>, value: &String) {
+    if !values.contains(value) {
+        values.push(value.clone());
+    }
+}
+
+impl fmt::Display for PrivacyReport {
+    /// Human-readable form of the report.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} SDKs, {} without a privacy manifest", self.sdks.len(), self.missing_manifest.len())?;
+        for sdk in &self.sdks {
+            let mut notes = Vec::new();
+            if !sdk.has_manifest {
+                notes.push("no privacy manifest");
+            }
+            if sdk.tracking {
+                notes.push("tracking");
+            }
+            write!(f, "  {} ({})", sdk.name, sdk.path)?;
+            if !notes.is_empty() {
+                write!(f, " [{}]", notes.join(", "))?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "\ntracking: {}", if self.tracking { "yes" } else { "no" })?;
+
+        if !self.tracking_domains.is_empty() {
+            writeln!(f, "\ntracking domains:")?;
+            for domain in &self.tracking_domains {
+                writeln!(f, "  {} ({})", domain.domain, domain.sdks.join(", "))?;
+            }
+        }
+        if !self.collected_data_types.is_empty() {
+            writeln!(f, "\ncollected data types:")?;
+            for data_type in &self.collected_data_types {
+                let linked = if data_type.linked_to_user { "linked" } else { "not linked" };
+                let tracking = if data_type.tracking { ", tracking" } else { "" };
+                writeln!(f, "  {} ({}{}) ({})", data_type.data_type, linked, tracking, data_type.sdks.join(", "))?;
+                for purpose in &data_type.purposes {
+                    writeln!(f, "    {}", purpose)?;
+                }
+            }
+        }
+        if !self.accessed_api_types.is_empty() {
+            writeln!(f, "\naccessed API types:")?;
+            for api_type in &self.accessed_api_types {
+                writeln!(f, "  {}: {}", api_type.api, api_type.reasons.join(", "))?;
+                for sdk in &api_type.sdks {
+                    writeln!(f, "    {}: {}", sdk.sdk, sdk.reasons.join(", "))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
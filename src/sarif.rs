@@ -0,0 +1,212 @@
+use std::path::Path;
+use serde::Serialize;
+use crate::policy::{CheckReport, Finding, Severity};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const INFORMATION_URI: &str = "https://github.com/eaceto/xcspec";
+
+/// Rules of the policy, as `(rule id, short description)`; they are errors by default.
+pub const POLICY_RULES: &[(&str, &str)] = &[
+    ("required-platform", "The framework has a slice for every platform required by the policy"),
+    ("required-architecture", "Slices contain the architectures required by the policy"),
+    ("maximum-minimum-os", "The minimum OS of every slice is not above the one allowed by the policy"),
+    ("library-evolution", "The framework is built with library evolution (BUILD_LIBRARY_FOR_DISTRIBUTION)"),
+    ("privacy-manifest", "Every slice ships a privacy manifest"),
+    ("forbidden-dependency", "Slices do not import or link the dependencies forbidden by the policy"),
+    ("max-slice-size", "Binaries are not larger than the size allowed by the policy"),
+    ("signature", "The framework is signed and its signature is valid"),
+    ("team-id", "The framework is signed by one of the Team IDs allowed by the policy"),
+];
+
+/// Warning codes of the report, as `(code, short description)`; they are warnings by default.
+pub const WARNING_RULES: &[(&str, &str)] = &[
+    ("architecture-mismatch", "The architectures of the Info.plist match those of the binary"),
+    ("platform-mismatch", "The platform of every binary matches the platform of its slice"),
+    ("invalid-binary", "Binaries can be parsed as Mach-O files"),
+    ("mixed-linkage", "Binaries and slices share the same static or dynamic linkage"),
+    ("absolute-install-name", "Install names are relative (@rpath) rather than paths of the build machine"),
+    ("dsym-mismatch", "dSYMs match the UUIDs of the binaries"),
+    ("debug-build", "The Swift module is built with optimizations"),
+    ("testable-build", "The Swift module is not built with -enable-testing"),
+    ("swift-details-mismatch", "Slices agree on the Swift compiler, version and library evolution"),
+    ("invalid-signature", "Code signatures can be parsed"),
+    ("signature-tampered", "Files match the hashes sealed by the code signature"),
+    ("invalid-module-map", "Module maps can be parsed"),
+    ("missing-module-map", "Public headers are exposed by a module map"),
+    ("missing-umbrella-header", "The umbrella header of every module exists"),
+    ("missing-header", "The headers listed by a module map exist"),
+    ("header-not-in-umbrella", "Public headers are included by the umbrella header"),
+    ("non-modular-include", "Headers only include headers of their module or of other modules"),
+    ("non-system-framework-import", "Headers only import system frameworks"),
+    ("invalid-privacy-manifest", "Privacy manifests can be parsed"),
+    ("unknown-privacy-key", "Privacy manifests only use keys known to Xcode"),
+    ("invalid-privacy-value", "Privacy manifests only use Apple's data types, purposes, API categories and reasons"),
+    ("duplicate-privacy-entry", "Privacy manifests declare every entry once"),
+    ("tracking-without-domains", "Privacy manifests declaring tracking list their tracking domains"),
+    ("malformed-privacy-entry", "Entries of privacy manifests have every required key with the right type"),
+    ("undeclared-required-reason-api", "Required reason APIs referenced by a binary are declared in its privacy manifest"),
+    ("unused-required-reason-api", "Required reason API categories declared in a privacy manifest are referenced by the binary"),
+    ("missing-required-privacy-manifest", "Commonly used third-party SDKs ship a privacy manifest in every slice"),
+    ("missing-required-signature", "Commonly used third-party SDKs are signed"),
+];
+
+/// SARIF 2.1.0 log of the findings of one or more frameworks, for code scanning dashboards.
+#[derive(Serialize, Debug)]
+pub struct SarifLog {
+    #[serde(rename = "$schema")]
+    pub schema: String,
+    pub version: String,
+    pub runs: Vec<SarifRun>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SarifRun {
+    pub tool: SarifTool,
+    pub results: Vec<SarifResult>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SarifTool {
+    pub driver: SarifDriver,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifDriver {
+    pub name: String,
+    pub version: String,
+    pub information_uri: String,
+    pub rules: Vec<SarifRule>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifRule {
+    pub id: String,
+    pub short_description: SarifMessage,
+    pub default_configuration: SarifConfiguration,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SarifConfiguration {
+    pub level: Severity,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SarifMessage {
+    pub text: String,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifResult {
+    pub rule_id: String,
+    pub rule_index: usize,
+    /// SARIF levels share the names of the severities.
+    pub level: Severity,
+    pub message: SarifMessage,
+    pub locations: Vec<SarifLocation>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifLocation {
+    pub physical_location: SarifPhysicalLocation,
+    /// Path inside a zipped framework, which has no file of its own.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub logical_locations: Vec<SarifLogicalLocation>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifPhysicalLocation {
+    pub artifact_location: SarifArtifactLocation,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SarifArtifactLocation {
+    pub uri: String,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifLogicalLocation {
+    pub fully_qualified_name: String,
+}
+
+/// Converts findings to a SARIF log. Every known check is described in the rules of the
+/// tool; a finding is located at its file when the framework is a directory, and at the
+/// framework otherwise.
+pub fn sarif_log(report: &CheckReport) -> SarifLog {
+    let policy_rules = POLICY_RULES.iter().map(|rule| (rule, Severity::Error));
+    let warning_rules = WARNING_RULES.iter().map(|rule| (rule, Severity::Warning));
+    let mut rules: Vec<SarifRule> = policy_rules
+        .chain(warning_rules)
+        .map(|((id, description), level)| SarifRule {
+            id: id.to_string(),
+            short_description: SarifMessage { text: description.to_string() },
+            default_configuration: SarifConfiguration { level },
+        })
+        .collect();
+
+    let mut results = Vec::new();
+    for finding in &report.findings {
+        let rule_index = match rules.iter().position(|rule| rule.id == finding.rule) {
+            Some(index) => index,
+            None => {
+                rules.push(SarifRule {
+                    id: finding.rule.clone(),
+                    short_description: SarifMessage { text: finding.rule.clone() },
+                    default_configuration: SarifConfiguration { level: Severity::Warning },
+                });
+                rules.len() - 1
+            }
+        };
+        results.push(SarifResult {
+            rule_id: finding.rule.clone(),
+            rule_index,
+            level: finding.severity,
+            message: SarifMessage { text: finding.message.clone() },
+            locations: vec![location(finding)],
+        });
+    }
+
+    SarifLog {
+        schema: SARIF_SCHEMA.to_string(),
+        version: "2.1.0".to_string(),
+        runs: vec![SarifRun {
+            tool: SarifTool {
+                driver: SarifDriver {
+                    name: env!("CARGO_PKG_NAME").to_string(),
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                    information_uri: INFORMATION_URI.to_string(),
+                    rules,
+                },
+            },
+            results,
+        }],
+    }
+}
+
+/// Paths of findings start at the root of the archive, e.g. `Foo.xcframework/ios-arm64/...`,
+/// which sits next to an expanded framework on disk.
+fn location(finding: &Finding) -> SarifLocation {
+    let framework = Path::new(&finding.framework);
+    let (uri, logical_locations) = match &finding.path {
+        Some(path) if framework.is_dir() => {
+            let parent = framework.parent().unwrap_or(Path::new(""));
+            (parent.join(path.trim_end_matches('/')).display().to_string(), Vec::new())
+        }
+        Some(path) => (
+            finding.framework.clone(),
+            vec![SarifLogicalLocation { fully_qualified_name: path.clone() }],
+        ),
+        None => (finding.framework.clone(), Vec::new()),
+    };
+    SarifLocation {
+        physical_location: SarifPhysicalLocation {
+            artifact_location: SarifArtifactLocation { uri },
+        },
+        logical_locations,
+    }
+}
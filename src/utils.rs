@@ -1,6 +1,6 @@
-use std::fs::File;
-use zip::ZipArchive;
+use std::cmp::Ordering;
 use indexmap::IndexSet;
+use crate::xcframework_processing::archive_index::ArchiveIndex;
 
 /// Ensures the elements in the vector are unique while preserving the order.
 pub fn ensure_unique_ordered(vec: Vec<String>) -> Vec<String> {
@@ -8,22 +8,79 @@ pub fn ensure_unique_ordered(vec: Vec<String>) -> Vec<String> {
     set.into_iter().collect()
 }
 
-/// Calculates the size of a library within a zip archive.
+/// Calculates the size in bytes of a library binary within a slice.
 pub fn calculate_library_size(
-    archive: &mut ZipArchive<File>,
+    index: &ArchiveIndex,
+    library_identifier: &str,
     binary_path: &str,
-) -> Result<String, String> {
-    for i in 0..archive.len() {
-        let file = archive.by_index(i)
-            .map_err(|e| format!("Failed to access file in archive: {}", e))?;
-        if file.name().ends_with(binary_path) {
-            let size = file.size();
-            return Ok(if size >= 1_000_000 {
-                format!("{:.2} MB", size as f64 / 1_000_000.0)
-            } else {
-                format!("{:.2} KB", size as f64 / 1_000.0)
-            });
+) -> Result<u64, String> {
+    let file = index
+        .get(&index.slice_path(library_identifier, binary_path))
+        .ok_or_else(|| format!("Binary file '{}' not found in archive.", binary_path))?;
+    Ok(file.size)
+}
+
+/// Formats a size in bytes as KB or MB, as shown in the reports.
+pub fn format_size(size: u64) -> String {
+    if size >= 1_000_000 {
+        format!("{:.2} MB", size as f64 / 1_000_000.0)
+    } else {
+        format!("{:.2} KB", size as f64 / 1_000.0)
+    }
+}
+
+/// Parses a size written in bytes or with a `KB`, `MB` or `GB` unit (e.g. `"50 MB"`), as
+/// [`format_size`] writes them.
+pub fn parse_size(size: &str) -> Option<u64> {
+    let size = size.trim();
+    let split = size.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(size.len());
+    let (number, unit) = size.split_at(split);
+    let multiplier = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1.0,
+        "KB" => 1_000.0,
+        "MB" => 1_000_000.0,
+        "GB" => 1_000_000_000.0,
+        _ => return None,
+    };
+    number.parse::<f64>().ok().map(|number| (number * multiplier).round() as u64)
+}
+
+/// Compares two dotted version strings numerically (`"9.0" < "10.0"`, `"17" == "17.0"`).
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parse = |version: &str| -> Vec<u64> {
+        version.split('.').map(|part| part.trim().parse().unwrap_or(0)).collect()
+    };
+    let (a, b) = (parse(a), parse(b));
+    for i in 0..a.len().max(b.len()) {
+        let ordering = a.get(i).unwrap_or(&0).cmp(b.get(i).unwrap_or(&0));
+        if ordering != Ordering::Equal {
+            return ordering;
         }
     }
-    Err(format!("Binary file '{}' not found in archive.", binary_path))
+    Ordering::Equal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sizes_with_units() {
+        assert_eq!(parse_size("50 MB"), Some(50_000_000));
+        assert_eq!(parse_size("1.5GB"), Some(1_500_000_000));
+        assert_eq!(parse_size("512kb"), Some(512_000));
+        assert_eq!(parse_size("2048"), Some(2048));
+        assert_eq!(parse_size(" 10 B "), Some(10));
+        assert_eq!(parse_size(&format_size(12_340_000)), Some(12_340_000));
+    }
+
+    #[test]
+    fn rejects_invalid_sizes() {
+        assert_eq!(parse_size("50 MiB"), None);
+        assert_eq!(parse_size("50 TB"), None);
+        assert_eq!(parse_size("MB"), None);
+        assert_eq!(parse_size("1.2.3 MB"), None);
+        assert_eq!(parse_size("-5 MB"), None);
+        assert_eq!(parse_size(""), None);
+    }
 }
@@ -0,0 +1,158 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Bound;
+use crate::xcframework_processing::archive_source::ArchiveEntry;
+
+/// Kind of file, as far as the extractors are concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    InfoPlist,
+    PrivacyManifest,
+    SwiftInterface,
+    ModuleMap,
+    Header,
+    Other,
+}
+
+impl FileKind {
+    fn from_name(name: &str) -> FileKind {
+        if name.ends_with("Info.plist") {
+            FileKind::InfoPlist
+        } else if name.ends_with("PrivacyInfo.xcprivacy") {
+            FileKind::PrivacyManifest
+        } else if name.ends_with(".swiftinterface") {
+            FileKind::SwiftInterface
+        } else if name.ends_with(".modulemap") {
+            FileKind::ModuleMap
+        } else if name.ends_with(".h") {
+            FileKind::Header
+        } else {
+            FileKind::Other
+        }
+    }
+}
+
+/// Metadata of a file in the archive.
+#[derive(Debug, Clone)]
+pub struct IndexedFile {
+    pub name: String,
+    pub size: u64,
+    pub kind: FileKind,
+    /// Library identifier of the slice the file belongs to, if any.
+    pub slice: Option<String>,
+}
+
+/// One-time index of an archive, built from a single listing of its entries.
+///
+/// Extractors query the index instead of scanning the archive, so the cost of
+/// walking the central directory (or the filesystem) is paid once per framework.
+pub struct ArchiveIndex {
+    root: String,
+    files: Vec<IndexedFile>,
+    /// Sorted, so that the files under a directory are a contiguous range.
+    by_name: BTreeMap<String, usize>,
+    by_slice: HashMap<String, Vec<usize>>,
+}
+
+impl ArchiveIndex {
+    pub fn build(entries: Vec<ArchiveEntry>) -> ArchiveIndex {
+        // The xcframework's own Info.plist is the shallowest one; slices carry their own deeper down.
+        let root = entries
+            .iter()
+            .filter(|entry| FileKind::from_name(&entry.name) == FileKind::InfoPlist)
+            .min_by_key(|entry| entry.name.matches('/').count())
+            .map(|entry| match entry.name.rfind('/') {
+                Some(position) => entry.name[..=position].to_string(),
+                None => String::new(),
+            })
+            .unwrap_or_default();
+
+        let mut files = Vec::with_capacity(entries.len());
+        let mut by_name = BTreeMap::new();
+        let mut by_slice: HashMap<String, Vec<usize>> = HashMap::new();
+
+        for entry in entries {
+            let kind = FileKind::from_name(&entry.name);
+            let slice = entry.name
+                .strip_prefix(&root)
+                .and_then(|relative| relative.split_once('/'))
+                .map(|(identifier, _)| identifier.to_string());
+
+            let position = files.len();
+            if let Some(identifier) = &slice {
+                by_slice.entry(identifier.clone()).or_default().push(position);
+            }
+            by_name.insert(entry.name.clone(), position);
+            files.push(IndexedFile { name: entry.name, size: entry.size, kind, slice });
+        }
+
+        ArchiveIndex { root, files, by_name, by_slice }
+    }
+
+    /// Returns the top-level Info.plist of the xcframework.
+    pub fn root_info_plist(&self) -> Option<&IndexedFile> {
+        self.get(&format!("{}Info.plist", self.root))
+    }
+
+    /// Looks a file up by its exact name.
+    pub fn get(&self, name: &str) -> Option<&IndexedFile> {
+        self.by_name.get(name).map(|&position| &self.files[position])
+    }
+
+    /// Returns every file of a given kind, in archive order.
+    pub fn files_of_kind(&self, kind: FileKind) -> impl Iterator<Item = &IndexedFile> {
+        self.files.iter().filter(move |file| file.kind == kind)
+    }
+
+    /// Returns every file belonging to a slice, in archive order.
+    pub fn slice_files(&self, library_identifier: &str) -> impl Iterator<Item = &IndexedFile> {
+        self.by_slice
+            .get(library_identifier)
+            .into_iter()
+            .flatten()
+            .map(|&position| &self.files[position])
+    }
+
+    /// Returns the files of a slice located under `relative_path` (e.g. `Foo.framework/Modules`).
+    pub fn library_files<'a>(
+        &'a self,
+        library_identifier: &str,
+        relative_path: &str,
+    ) -> impl Iterator<Item = &'a IndexedFile> {
+        let prefix = format!("{}/", self.slice_path(library_identifier, relative_path));
+        self.slice_files(library_identifier).filter(move |file| file.name.starts_with(&prefix))
+    }
+
+    /// Returns every file whose name starts with `prefix`, in name order.
+    pub fn files_under<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = &'a IndexedFile> {
+        self.by_name
+            .range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+            .take_while(move |(name, _)| name.starts_with(prefix))
+            .map(|(_, &position)| &self.files[position])
+    }
+
+    /// Builds the full name of a path relative to the xcframework.
+    pub fn root_path(&self, relative_path: &str) -> String {
+        format!("{}{}", self.root, relative_path)
+    }
+
+    /// Builds the full name of a path relative to a slice.
+    pub fn slice_path(&self, library_identifier: &str, relative_path: &str) -> String {
+        format!("{}{}/{}", self.root, library_identifier, relative_path.trim_end_matches('/'))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_files_under_a_prefix() {
+        let names = ["Foo.xcframework/Info.plist", "Foo.xcframework/a/b", "Foo.xcframework/a/c", "Foo.xcframework/ab", "Foo.xcframework/b"];
+        let entries = names.iter().map(|name| ArchiveEntry { name: name.to_string(), size: 0 }).collect();
+        let index = ArchiveIndex::build(entries);
+        let under: Vec<&str> = index.files_under("Foo.xcframework/a/").map(|file| file.name.as_str()).collect();
+        assert_eq!(under, ["Foo.xcframework/a/b", "Foo.xcframework/a/c"]);
+        assert_eq!(index.files_under("Foo.xcframework/").count(), 5);
+        assert_eq!(index.files_under("Bar").count(), 0);
+    }
+}
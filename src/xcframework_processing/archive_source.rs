@@ -0,0 +1,192 @@
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+
+/// A regular file listed by an [`ArchiveSource`].
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    pub name: String,
+    pub size: u64,
+}
+
+/// Read-only access to the files of a framework, regardless of how it is stored.
+///
+/// File names are always relative and use `/` as separator, so extractors can
+/// match paths the same way for zipped and expanded frameworks.
+pub trait ArchiveSource {
+    /// Lists every regular file in the source with its uncompressed size, in a single pass.
+    fn entries(&mut self) -> Result<Vec<ArchiveEntry>, String>;
+
+    /// Reads the whole content of a file.
+    fn read_file(&mut self, name: &str) -> Result<Vec<u8>, String>;
+
+    /// Reads the whole content of a file as UTF-8 text.
+    fn read_to_string(&mut self, name: &str) -> Result<String, String> {
+        let buffer = self.read_file(name)?;
+        String::from_utf8(buffer).map_err(|e| format!("File '{}' is not valid UTF-8: {}", name, e))
+    }
+}
+
+/// Opens the framework at `path`, picking the backend from the kind of path:
+/// directories (e.g. an expanded `.xcframework`) are read from the filesystem,
+/// anything else is treated as a zip file.
+pub fn open_archive<P: AsRef<Path>>(path: P) -> Result<Box<dyn ArchiveSource>, String> {
+    let path = path.as_ref();
+    if path.is_dir() {
+        Ok(Box::new(DirectorySource::new(path)?))
+    } else {
+        Ok(Box::new(ZipSource::new(path)?))
+    }
+}
+
+/// Expands a path into the frameworks it holds: an `.xcframework` directory or a file is
+/// returned as is, while any other directory is searched for `.xcframework` directories
+/// and zip files.
+pub fn find_archives<P: AsRef<Path>>(path: P) -> Result<Vec<PathBuf>, String> {
+    let path = path.as_ref();
+    if !path.is_dir() || path.extension().is_some_and(|extension| extension == "xcframework") {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut archives = Vec::new();
+    collect_archives(path, &mut archives)?;
+    archives.sort();
+    Ok(archives)
+}
+
+fn collect_archives(directory: &Path, archives: &mut Vec<PathBuf>) -> Result<(), String> {
+    let dir_entries = fs::read_dir(directory)
+        .map_err(|e| format!("Failed to read directory '{}': {}", directory.display(), e))?;
+    for entry in dir_entries {
+        let path = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?.path();
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        if (path.is_dir() && extension == Some("xcframework")) || (path.is_file() && extension == Some("zip")) {
+            archives.push(path);
+        } else if path.is_dir() && !path.is_symlink() {
+            collect_archives(&path, archives)?;
+        }
+    }
+    Ok(())
+}
+
+/// Backend for zipped frameworks.
+pub struct ZipSource {
+    archive: ZipArchive<File>,
+}
+
+impl ZipSource {
+    pub fn new<P: AsRef<Path>>(zip_path: P) -> Result<Self, String> {
+        let file = File::open(&zip_path).map_err(|e| format!("Failed to open the zip file: {}", e))?;
+        let archive = ZipArchive::new(file).map_err(|e| format!("Failed to read the zip file: {}", e))?;
+        Ok(ZipSource { archive })
+    }
+}
+
+impl ArchiveSource for ZipSource {
+    fn entries(&mut self) -> Result<Vec<ArchiveEntry>, String> {
+        let mut entries = Vec::with_capacity(self.archive.len());
+        for i in 0..self.archive.len() {
+            let file = self.archive
+                .by_index_raw(i)
+                .map_err(|e| format!("Failed to access file in archive: {}", e))?;
+            if !file.is_dir() {
+                entries.push(ArchiveEntry { name: file.name().to_string(), size: file.size() });
+            }
+        }
+        Ok(entries)
+    }
+
+    fn read_file(&mut self, name: &str) -> Result<Vec<u8>, String> {
+        let mut file = self.archive
+            .by_name(name)
+            .map_err(|e| format!("Failed to access file in archive: {}", e))?;
+        let mut buffer = Vec::new();
+        file.read_to_end(&mut buffer).map_err(|e| format!("Failed to read file content: {}", e))?;
+        Ok(buffer)
+    }
+}
+
+/// Backend for frameworks expanded on disk, such as build outputs or SPM artifact caches.
+///
+/// Names are relative to the parent of the given directory, so an expanded
+/// `Foo.xcframework` yields the same `Foo.xcframework/...` names as its zip.
+pub struct DirectorySource {
+    base: PathBuf,
+    entries: Vec<ArchiveEntry>,
+}
+
+impl DirectorySource {
+    pub fn new<P: AsRef<Path>>(directory: P) -> Result<Self, String> {
+        let directory = directory.as_ref();
+        let base = directory.parent().map(Path::to_path_buf).unwrap_or_default();
+        let mut entries = Vec::new();
+        collect_entries(&base, directory, &mut entries)?;
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(DirectorySource { base, entries })
+    }
+
+    fn full_path(&self, name: &str) -> PathBuf {
+        self.base.join(name)
+    }
+}
+
+impl ArchiveSource for DirectorySource {
+    fn entries(&mut self) -> Result<Vec<ArchiveEntry>, String> {
+        Ok(self.entries.clone())
+    }
+
+    fn read_file(&mut self, name: &str) -> Result<Vec<u8>, String> {
+        fs::read(self.full_path(name)).map_err(|e| format!("Failed to read file '{}': {}", name, e))
+    }
+}
+
+/// Recursively collects the files under `directory`, relative to `base`.
+///
+/// Symbolic links to directories (e.g. `Versions/Current`) are not followed, mirroring
+/// how they are stored in a zip, so every file is listed once.
+fn collect_entries(base: &Path, directory: &Path, entries: &mut Vec<ArchiveEntry>) -> Result<(), String> {
+    let dir_entries = fs::read_dir(directory)
+        .map_err(|e| format!("Failed to read directory '{}': {}", directory.display(), e))?;
+    for entry in dir_entries {
+        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let path = entry.path();
+        let file_type = entry.file_type().map_err(|e| format!("Failed to read file type: {}", e))?;
+        if file_type.is_dir() {
+            collect_entries(base, &path, entries)?;
+        } else if file_type.is_file() || (file_type.is_symlink() && path.is_file()) {
+            let relative = path.strip_prefix(base).unwrap_or(&path);
+            let name = relative
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let size = fs::metadata(&path)
+                .map(|metadata| metadata.len())
+                .map_err(|e| format!("Failed to access file '{}': {}", name, e))?;
+            entries.push(ArchiveEntry { name, size });
+        }
+    }
+    Ok(())
+}
+
+/// In-memory archive for tests, keyed by file name.
+#[cfg(test)]
+pub(crate) struct MemorySource(std::collections::BTreeMap<String, Vec<u8>>);
+
+#[cfg(test)]
+impl MemorySource {
+    pub fn new<N: Into<String>, C: Into<Vec<u8>>>(files: impl IntoIterator<Item = (N, C)>) -> MemorySource {
+        MemorySource(files.into_iter().map(|(name, content)| (name.into(), content.into())).collect())
+    }
+}
+
+#[cfg(test)]
+impl ArchiveSource for MemorySource {
+    fn entries(&mut self) -> Result<Vec<ArchiveEntry>, String> {
+        Ok(self.0.iter().map(|(name, content)| ArchiveEntry { name: name.clone(), size: content.len() as u64 }).collect())
+    }
+
+    fn read_file(&mut self, name: &str) -> Result<Vec<u8>, String> {
+        self.0.get(name).cloned().ok_or_else(|| format!("{} not found", name))
+    }
+}
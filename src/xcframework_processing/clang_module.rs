@@ -0,0 +1,726 @@
+use crate::framework_info::{ClangModule, ClangModuleInfo, ModuleLink, Warning};
+use crate::xcframework_processing::archive_index::{ArchiveIndex, FileKind};
+use crate::xcframework_processing::archive_source::ArchiveSource;
+
+/// Frameworks, modules and include directories of the Apple SDKs that public headers may
+/// import, compared case-insensitively like the file systems of macOS.
+const SYSTEM_MODULES: &[&str] = &[
+    "Accelerate", "Accessibility", "Accounts", "ActivityKit", "AddressBook", "AddressBookUI", "AdSupport",
+    "AppIntents", "AppKit", "AppleArchive", "AppTrackingTransparency", "ARKit", "AssetsLibrary", "AudioToolbox",
+    "AudioUnit", "AuthenticationServices", "AVFAudio", "AVFoundation", "AVKit", "AVRouting", "BackgroundTasks",
+    "BusinessChat", "CallKit", "Carbon", "CarPlay", "CFNetwork", "Charts", "ClassKit", "CloudKit", "Cocoa",
+    "ColorSync", "Combine", "CommonCrypto", "Compression", "Contacts", "ContactsUI", "CoreAudio", "CoreAudioKit",
+    "CoreAudioTypes", "CoreBluetooth", "CoreData", "CoreFoundation", "CoreGraphics", "CoreHaptics", "CoreImage",
+    "CoreLocation", "CoreMedia", "CoreMIDI", "CoreML", "CoreMotion", "CoreNFC", "CoreServices", "CoreSpotlight",
+    "CoreTelephony", "CoreText", "CoreVideo", "CoreWLAN", "CryptoKit", "CryptoTokenKit", "Darwin",
+    "DeviceCheck", "DiskArbitration", "Dispatch", "EventKit", "EventKitUI", "ExternalAccessory", "FileProvider",
+    "Foundation", "GameController", "GameKit", "GameplayKit", "GLKit", "HealthKit", "HomeKit",
+    "ImageCaptureCore", "ImageIO", "Intents", "IntentsUI", "IOKit", "IOSurface", "JavaScriptCore",
+    "LinkPresentation", "LocalAuthentication", "MapKit", "MediaPlayer", "MediaToolbox", "MessageUI", "Messages", "Metal",
+    "MetalKit", "MetalPerformanceShaders", "MetricKit", "MobileCoreServices", "ModelIO",
+    "MultipeerConnectivity", "NaturalLanguage", "Network", "NetworkExtension", "NotificationCenter",
+    "ObjectiveC", "Observation", "OpenGL", "OpenGLES", "OSLog", "PassKit", "PDFKit", "PencilKit", "Photos",
+    "PhotosUI", "PushKit", "QuartzCore", "QuickLook", "QuickLookThumbnailing", "RealityKit", "ReplayKit",
+    "SafariServices", "SceneKit", "ScreenCaptureKit", "Security", "ServiceManagement", "SensorKit", "Social",
+    "Speech", "SpriteKit", "StoreKit", "SwiftData", "SwiftUI", "SystemConfiguration", "TVServices", "TVUIKit",
+    "UIKit", "UniformTypeIdentifiers", "UserNotifications", "UserNotificationsUI", "VideoToolbox", "Vision",
+    "VisionKit", "WatchConnectivity", "WatchKit", "WebKit", "WidgetKit", "XCTest", "zlib", "arpa", "libkern",
+    "libxml", "libxml2", "mach", "mach-o", "machine", "malloc", "net", "netinet", "netinet6", "objc", "os",
+    "simd", "sys", "uuid", "xpc",
+];
+
+/// Reads the module maps and headers of a slice and checks that the module is
+/// self-contained. Framework headers live in `Headers`/`PrivateHeaders`; library
+/// slices keep them (and their module map) in `headers_path`.
+pub fn extract_clang_module(
+    archive: &mut dyn ArchiveSource,
+    index: &ArchiveIndex,
+    library_identifier: &str,
+    library_path: &str,
+    headers_path: Option<&str>,
+    warnings: &mut Vec<Warning>,
+) -> Option<ClangModuleInfo> {
+    let is_framework = library_path.ends_with(".framework");
+    let (public_directory, private_directory, modules_directory) = if is_framework {
+        let public_directory = format!("{}/Headers", library_path);
+        (public_directory, Some(format!("{}/PrivateHeaders", library_path)), format!("{}/Modules", library_path))
+    } else {
+        let headers_path = headers_path?.to_string();
+        (headers_path.clone(), None, headers_path)
+    };
+
+    let slice_prefix = index.slice_path(library_identifier, "");
+    let relative = |name: &str, directory: &str| -> String {
+        let prefix = format!("{}/", directory);
+        name.strip_prefix(&slice_prefix).unwrap_or(name).strip_prefix(&prefix).unwrap_or(name).to_string()
+    };
+    let headers_in = |directory: &str| -> Vec<String> {
+        index
+            .library_files(library_identifier, directory)
+            .filter(|file| file.kind == FileKind::Header)
+            .map(|file| relative(&file.name, directory))
+            .collect()
+    };
+    let public_headers = headers_in(&public_directory);
+    let private_headers = private_directory.as_deref().map(headers_in).unwrap_or_default();
+    let module_map_names: Vec<String> = index
+        .library_files(library_identifier, &modules_directory)
+        .filter(|file| file.kind == FileKind::ModuleMap)
+        .map(|file| file.name.clone())
+        .collect();
+    if module_map_names.is_empty() && public_headers.is_empty() && private_headers.is_empty() {
+        return None;
+    }
+
+    let mut info = ClangModuleInfo { public_headers, private_headers, ..Default::default() };
+    for name in module_map_names {
+        match archive.read_to_string(&name) {
+            Ok(contents) => info.modules.extend(parse_module_map(&contents)),
+            Err(e) => warnings.push(Warning { code: "invalid-module-map".to_string(), message: e, path: Some(name.clone()) }),
+        }
+        info.module_maps.push(name.strip_prefix(&slice_prefix).unwrap_or(&name).to_string());
+    }
+
+    let public_path = index.slice_path(library_identifier, &public_directory);
+    if info.module_maps.is_empty() && !info.public_headers.is_empty() {
+        warnings.push(Warning {
+            code: "missing-module-map".to_string(),
+            message: "Public headers are not exposed by a module map, so the library cannot be imported as a module".to_string(),
+            path: Some(public_path.clone()),
+        });
+    }
+
+    let checker = HeaderChecker {
+        framework_name: library_path.trim_end_matches(".framework"),
+        public_path: &public_path,
+        private_path: private_directory.map(|directory| index.slice_path(library_identifier, &directory)),
+        info: &info,
+    };
+    checker.check_module_map(warnings);
+    checker.check_includes(archive, warnings);
+    Some(info)
+}
+
+/// Parses the module declarations of a module map. Unknown members are skipped.
+pub fn parse_module_map(contents: &str) -> Vec<ClangModule> {
+    let tokens = tokenize(contents);
+    let mut parser = ModuleMapParser { tokens, position: 0 };
+    let mut modules = Vec::new();
+    while parser.position < parser.tokens.len() {
+        if parser.extern_module() {
+            continue;
+        }
+        match parser.module() {
+            Some(module) => modules.push(module),
+            None => parser.position += 1,
+        }
+    }
+    modules
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Identifier(String),
+    String(String),
+    Punctuation(char),
+}
+
+fn tokenize(contents: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut characters = contents.chars().peekable();
+    while let Some(character) = characters.next() {
+        match character {
+            '/' if characters.peek() == Some(&'/') => {
+                for next in characters.by_ref() {
+                    if next == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if characters.peek() == Some(&'*') => {
+                characters.next();
+                let mut previous = ' ';
+                for next in characters.by_ref() {
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+            '"' => {
+                let mut value = String::new();
+                while let Some(next) = characters.next() {
+                    match next {
+                        '"' => break,
+                        '\\' => value.extend(characters.next()),
+                        _ => value.push(next),
+                    }
+                }
+                tokens.push(Token::String(value));
+            }
+            _ if character.is_alphanumeric() || character == '_' => {
+                let mut value = character.to_string();
+                while let Some(&next) = characters.peek() {
+                    if !(next.is_alphanumeric() || next == '_') {
+                        break;
+                    }
+                    value.push(next);
+                    characters.next();
+                }
+                tokens.push(Token::Identifier(value));
+            }
+            _ if character.is_whitespace() => {}
+            _ => tokens.push(Token::Punctuation(character)),
+        }
+    }
+    tokens
+}
+
+struct ModuleMapParser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl ModuleMapParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        if self.peek() == Some(&Token::Identifier(keyword.to_string())) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn punctuation(&mut self, punctuation: char) -> bool {
+        if self.peek() == Some(&Token::Punctuation(punctuation)) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn string(&mut self) -> Option<String> {
+        match self.peek() {
+            Some(Token::String(value)) => {
+                let value = value.clone();
+                self.position += 1;
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    /// Reads a dotted module identifier (`Foo.Bar`), or `*`.
+    fn module_id(&mut self) -> Option<String> {
+        if self.punctuation('*') {
+            return Some("*".to_string());
+        }
+        let mut id = match self.peek() {
+            Some(Token::Identifier(name)) => name.clone(),
+            _ => return None,
+        };
+        self.position += 1;
+        while self.peek() == Some(&Token::Punctuation('.')) {
+            match self.tokens.get(self.position + 1) {
+                Some(Token::Identifier(name)) => {
+                    id = format!("{}.{}", id, name);
+                    self.position += 2;
+                }
+                Some(Token::Punctuation('*')) => {
+                    id.push_str(".*");
+                    self.position += 2;
+                }
+                _ => break,
+            }
+        }
+        Some(id)
+    }
+
+    /// Skips a `{ ... }` block, such as the attributes of a header.
+    fn skip_block(&mut self) {
+        if !self.punctuation('{') {
+            return;
+        }
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next() {
+                Some(Token::Punctuation('{')) => depth += 1,
+                Some(Token::Punctuation('}')) => depth -= 1,
+                None => return,
+                _ => {}
+            }
+        }
+    }
+
+    /// Skips `extern module Foo "path"`, which points at another module map.
+    fn extern_module(&mut self) -> bool {
+        if !self.keyword("extern") {
+            return false;
+        }
+        if self.keyword("module") {
+            self.module_id();
+            self.string();
+        }
+        true
+    }
+
+    /// Parses `[explicit] [framework] module name [attributes] { members }`.
+    fn module(&mut self) -> Option<ClangModule> {
+        let start = self.position;
+        let mut module = ClangModule::default();
+        loop {
+            if self.keyword("explicit") {
+                module.explicit = true;
+            } else if self.keyword("framework") {
+                module.framework = true;
+            } else {
+                break;
+            }
+        }
+        if !self.keyword("module") {
+            self.position = start;
+            return None;
+        }
+        module.name = self.module_id()?;
+        while self.punctuation('[') {
+            while let Some(token) = self.next() {
+                match token {
+                    Token::Identifier(attribute) => module.attributes.push(attribute),
+                    Token::Punctuation(']') => break,
+                    _ => {}
+                }
+            }
+        }
+        if !self.punctuation('{') {
+            return Some(module);
+        }
+
+        while let Some(token) = self.peek().cloned() {
+            if token == Token::Punctuation('}') {
+                self.position += 1;
+                break;
+            }
+            if self.extern_module() {
+                continue;
+            }
+            if let Token::Identifier(word) = &token {
+                if matches!(word.as_str(), "explicit" | "framework" | "module") {
+                    if let Some(submodule) = self.module() {
+                        module.submodules.push(submodule);
+                        continue;
+                    }
+                }
+            }
+            self.member(&mut module);
+        }
+        Some(module)
+    }
+
+    fn member(&mut self, module: &mut ClangModule) {
+        let Some(Token::Identifier(word)) = self.next() else {
+            return;
+        };
+        match word.as_str() {
+            "umbrella" => {
+                if self.keyword("header") {
+                    module.umbrella_header = self.string();
+                    self.skip_block();
+                } else {
+                    module.umbrella_directory = self.string();
+                }
+            }
+            "private" | "textual" | "header" => {
+                let private = word == "private";
+                // `private textual header`
+                self.keyword("textual");
+                if word == "header" || self.keyword("header") {
+                    if let Some(header) = self.string() {
+                        if private {
+                            module.private_headers.push(header);
+                        } else {
+                            module.headers.push(header);
+                        }
+                    }
+                    self.skip_block();
+                }
+            }
+            "exclude" if self.keyword("header") => {
+                module.excluded_headers.extend(self.string());
+                self.skip_block();
+            }
+            "export" => module.exports.extend(self.module_id()),
+            "export_as" | "use" => {
+                self.module_id();
+            }
+            "link" => {
+                let framework = self.keyword("framework");
+                if let Some(name) = self.string() {
+                    module.link.push(ModuleLink { name, framework });
+                }
+            }
+            "requires" => loop {
+                let negated = self.punctuation('!');
+                match self.peek() {
+                    Some(Token::Identifier(feature)) => {
+                        module.requires.push(if negated { format!("!{}", feature) } else { feature.clone() });
+                        self.position += 1;
+                    }
+                    _ => break,
+                }
+                if !self.punctuation(',') {
+                    break;
+                }
+            },
+            "conflict" => {
+                self.module_id();
+                if self.punctuation(',') {
+                    self.string();
+                }
+            }
+            "config_macros" => {
+                while self.punctuation('[') {
+                    while !matches!(self.next(), Some(Token::Punctuation(']')) | None) {}
+                }
+                while let Some(Token::Identifier(_)) = self.peek() {
+                    self.position += 1;
+                    if !self.punctuation(',') {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `#import`/`#include` or `@import` directive of a header.
+struct Include {
+    target: String,
+    /// `"header.h"` rather than `<Module/header.h>`.
+    quoted: bool,
+    /// `@import Module;`
+    module_import: bool,
+}
+
+fn includes(contents: &str) -> Vec<Include> {
+    let mut includes = Vec::new();
+    for line in contents.lines() {
+        let line = line.trim_start();
+        if let Some(rest) = line.strip_prefix("@import") {
+            let module = rest.trim().trim_end_matches(';').trim();
+            if !module.is_empty() {
+                includes.push(Include { target: module.to_string(), quoted: false, module_import: true });
+            }
+            continue;
+        }
+        let Some(directive) = line.strip_prefix('#') else {
+            continue;
+        };
+        let directive = directive.trim_start();
+        let Some(rest) = ["import", "include_next", "include"].iter().find_map(|keyword| directive.strip_prefix(keyword)) else {
+            continue;
+        };
+        let rest = rest.trim_start();
+        let (quoted, close) = match rest.chars().next() {
+            Some('"') => (true, '"'),
+            Some('<') => (false, '>'),
+            _ => continue,
+        };
+        if let Some(end) = rest[1..].find(close) {
+            includes.push(Include { target: rest[1..=end].to_string(), quoted, module_import: false });
+        }
+    }
+    includes
+}
+
+struct HeaderChecker<'a> {
+    framework_name: &'a str,
+    public_path: &'a str,
+    private_path: Option<String>,
+    info: &'a ClangModuleInfo,
+}
+
+impl HeaderChecker<'_> {
+    /// Names the module can be imported as, which headers may include themselves through.
+    fn own_modules(&self) -> Vec<&str> {
+        let mut modules: Vec<&str> = self.info.modules.iter().map(|module| module.name.as_str()).collect();
+        modules.push(self.framework_name);
+        modules
+    }
+
+    fn has_header(&self, header: &str) -> bool {
+        self.info.public_headers.iter().chain(&self.info.private_headers).any(|existing| existing == header)
+    }
+
+    /// Checks that the headers referenced by the module maps exist.
+    fn check_module_map(&self, warnings: &mut Vec<Warning>) {
+        fn visit(checker: &HeaderChecker, module: &ClangModule, warnings: &mut Vec<Warning>) {
+            if let Some(umbrella) = &module.umbrella_header {
+                if !checker.has_header(umbrella) {
+                    warnings.push(Warning {
+                        code: "missing-umbrella-header".to_string(),
+                        message: format!("Umbrella header '{}' of module '{}' is missing", umbrella, module.name),
+                        path: Some(checker.public_path.to_string()),
+                    });
+                }
+            }
+            for header in module.headers.iter().chain(&module.private_headers) {
+                if !checker.has_header(header) {
+                    warnings.push(Warning {
+                        code: "missing-header".to_string(),
+                        message: format!("Header '{}' of module '{}' is missing", header, module.name),
+                        path: Some(checker.public_path.to_string()),
+                    });
+                }
+            }
+            for submodule in &module.submodules {
+                visit(checker, submodule, warnings);
+            }
+        }
+        for module in &self.info.modules {
+            visit(self, module, warnings);
+        }
+    }
+
+    /// Checks the includes of the public and private headers: headers of the module must
+    /// be part of it, and only system frameworks may be imported besides the module itself.
+    fn check_includes(&self, archive: &mut dyn ArchiveSource, warnings: &mut Vec<Warning>) {
+        let own_modules = self.own_modules();
+        let headers = self
+            .info
+            .public_headers
+            .iter()
+            .map(|header| (header, format!("{}/{}", self.public_path, header)))
+            .chain(self.info.private_headers.iter().filter_map(|header| {
+                self.private_path.as_ref().map(|path| (header, format!("{}/{}", path, header)))
+            }));
+
+        let mut included = Vec::new();
+        for (header, name) in headers.collect::<Vec<_>>() {
+            let Ok(contents) = archive.read_to_string(&name) else {
+                continue;
+            };
+            let directory = header.rsplit_once('/').map(|(directory, _)| directory);
+            for include in includes(&contents) {
+                let (module, file) = if include.module_import {
+                    (include.target.split('.').next().unwrap_or_default().to_string(), None)
+                } else if include.quoted {
+                    (String::new(), Some(include.target.clone()))
+                } else {
+                    match include.target.split_once('/') {
+                        Some((module, file)) => (module.to_string(), Some(file.to_string())),
+                        // `<stdio.h>` and other headers of the system search paths.
+                        None => continue,
+                    }
+                };
+
+                if include.quoted {
+                    let candidates = [directory.map(|directory| format!("{}/{}", directory, include.target)), Some(include.target.clone())];
+                    match candidates.into_iter().flatten().find(|candidate| self.has_header(candidate)) {
+                        Some(found) => included.push((header.clone(), found)),
+                        None => warnings.push(Warning {
+                            code: "non-modular-include".to_string(),
+                            message: format!("'{}' includes \"{}\", which is not part of the module", header, include.target),
+                            path: Some(name.clone()),
+                        }),
+                    }
+                } else if own_modules.contains(&module.as_str()) {
+                    if let Some(file) = file {
+                        if self.has_header(&file) {
+                            included.push((header.clone(), file));
+                        } else {
+                            warnings.push(Warning {
+                                code: "non-modular-include".to_string(),
+                                message: format!("'{}' includes <{}>, which is not part of the module", header, include.target),
+                                path: Some(name.clone()),
+                            });
+                        }
+                    }
+                } else if !SYSTEM_MODULES.iter().any(|system| system.eq_ignore_ascii_case(&module)) {
+                    warnings.push(Warning {
+                        code: "non-system-framework-import".to_string(),
+                        message: format!(
+                            "'{}' imports '{}', which is not a system framework; clients need it to build",
+                            header, module
+                        ),
+                        path: Some(name.clone()),
+                    });
+                }
+            }
+        }
+
+        // Public headers must be reachable from the umbrella header of the main module.
+        let Some(umbrella) = self.info.modules.first().and_then(|module| module.umbrella_header.clone()) else {
+            return;
+        };
+        if !self.has_header(&umbrella) {
+            return;
+        }
+        let mut pending = vec![umbrella.clone()];
+        let mut umbrella_includes = vec![umbrella];
+        while let Some(header) = pending.pop() {
+            for (_, file) in included.iter().filter(|(from, _)| *from == header) {
+                if !umbrella_includes.contains(file) {
+                    umbrella_includes.push(file.clone());
+                    pending.push(file.clone());
+                }
+            }
+        }
+        let listed = |header: &String| {
+            fn listed_in(module: &ClangModule, header: &String) -> bool {
+                module.headers.contains(header)
+                    || module.private_headers.contains(header)
+                    || module.excluded_headers.contains(header)
+                    || module.submodules.iter().any(|submodule| listed_in(submodule, header))
+            }
+            self.info.modules.iter().any(|module| listed_in(module, header))
+        };
+        for header in &self.info.public_headers {
+            if !umbrella_includes.contains(header) && !listed(header) {
+                warnings.push(Warning {
+                    code: "header-not-in-umbrella".to_string(),
+                    message: format!("Public header '{}' is not included by the umbrella header", header),
+                    path: Some(format!("{}/{}", self.public_path, header)),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xcframework_processing::archive_source::MemorySource;
+
+    const MODULE_MAP: &str = r#"
+        // Generated by Xcode.
+        framework module Foo [system] [extern_c] {
+            umbrella header "Foo.h"
+            extern module Nested "Nested.modulemap"
+            export *
+            module * { export * }
+
+            explicit module Private {
+                requires !objc, cplusplus
+                private header "Foo_Private.h"
+                textual header "Macros.h" { size 12 }
+            }
+            exclude header "Excluded.h"
+            link framework "UIKit"
+            link "z"
+        }
+        extern module Bar "Bar/module.modulemap"
+        /* module Commented { } */
+    "#;
+
+    #[test]
+    fn parses_framework_module() {
+        let modules = parse_module_map(MODULE_MAP);
+        assert_eq!(modules.len(), 1);
+        let module = &modules[0];
+        assert_eq!(module.name, "Foo");
+        assert!(module.framework && !module.explicit);
+        assert_eq!(module.attributes, ["system", "extern_c"]);
+        assert_eq!(module.umbrella_header.as_deref(), Some("Foo.h"));
+        assert_eq!(module.exports, ["*"]);
+        assert_eq!(module.excluded_headers, ["Excluded.h"]);
+        let links: Vec<(&str, bool)> = module.link.iter().map(|link| (link.name.as_str(), link.framework)).collect();
+        assert_eq!(links, [("UIKit", true), ("z", false)]);
+    }
+
+    #[test]
+    fn parses_submodules() {
+        let module = &parse_module_map(MODULE_MAP)[0];
+        let names: Vec<&str> = module.submodules.iter().map(|submodule| submodule.name.as_str()).collect();
+        assert_eq!(names, ["*", "Private"]);
+        assert_eq!(module.submodules[0].exports, ["*"]);
+        let private = &module.submodules[1];
+        assert!(private.explicit);
+        assert_eq!(private.requires, ["!objc", "cplusplus"]);
+        assert_eq!(private.private_headers, ["Foo_Private.h"]);
+        assert_eq!(private.headers, ["Macros.h"]);
+    }
+
+    #[test]
+    fn skips_extern_modules() {
+        let modules = parse_module_map("extern module Bar \"Bar.modulemap\"\nmodule Baz { header \"Baz.h\" }");
+        let names: Vec<&str> = modules.iter().map(|module| module.name.as_str()).collect();
+        assert_eq!(names, ["Baz"]);
+        assert_eq!(modules[0].headers, ["Baz.h"]);
+    }
+
+    #[test]
+    fn reads_quoted_angle_and_module_includes() {
+        let contents = "#import \"Foo/Bar.h\"\n  #  include <Foo/Baz.h>\n#include_next <stdio.h>\n@import UIKit.UIView;\n#define X 1\n";
+        let includes = includes(contents);
+        let includes: Vec<(&str, bool, bool)> =
+            includes.iter().map(|include| (include.target.as_str(), include.quoted, include.module_import)).collect();
+        assert_eq!(
+            includes,
+            [("Foo/Bar.h", true, false), ("Foo/Baz.h", false, false), ("stdio.h", false, false), ("UIKit.UIView", false, true)]
+        );
+    }
+
+    /// Warning codes of a `Foo.framework` slice with the given module map and public headers.
+    fn warnings(module_map: &str, headers: &[(&str, &str)]) -> Vec<(String, String)> {
+        let base = "Foo.xcframework/ios-arm64/Foo.framework";
+        let mut files = vec![
+            ("Foo.xcframework/Info.plist".to_string(), String::new()),
+            (format!("{}/Modules/module.modulemap", base), module_map.to_string()),
+        ];
+        files.extend(headers.iter().map(|(name, contents)| (format!("{}/Headers/{}", base, name), contents.to_string())));
+        let mut source = MemorySource::new(files);
+        let index = ArchiveIndex::build(source.entries().unwrap());
+        let mut warnings = Vec::new();
+        extract_clang_module(&mut source, &index, "ios-arm64", "Foo.framework", None, &mut warnings);
+        warnings.into_iter().map(|warning| (warning.code, warning.message)).collect()
+    }
+
+    #[test]
+    fn accepts_system_includes() {
+        let umbrella = "#import <objc/runtime.h>\n#include <dispatch/dispatch.h>\n#import <CommonCrypto/CommonDigest.h>\n\
+                        #include <arpa/inet.h>\n#include <net/if.h>\n#import <MediaToolbox/MediaToolbox.h>\n#import <uikit/UIKit.h>\n";
+        assert_eq!(warnings("framework module Foo { umbrella header \"Foo.h\" }", &[("Foo.h", umbrella)]), []);
+    }
+
+    #[test]
+    fn warns_about_non_system_and_non_modular_includes() {
+        let umbrella = "#import <Alamofire/Alamofire.h>\n#import \"Missing.h\"\n#import <Foo/Gone.h>\n";
+        let codes: Vec<String> = warnings("framework module Foo { umbrella header \"Foo.h\" }", &[("Foo.h", umbrella)])
+            .into_iter()
+            .map(|(code, _)| code)
+            .collect();
+        assert_eq!(codes, ["non-system-framework-import", "non-modular-include", "non-modular-include"]);
+    }
+
+    #[test]
+    fn warns_about_headers_unreachable_from_the_umbrella() {
+        let headers = [
+            ("Foo.h", "#import <Foo/Reached.h>\n"),
+            ("Reached.h", "#import \"Transitive.h\"\n"),
+            ("Transitive.h", ""),
+            ("Listed.h", ""),
+            ("Orphan.h", ""),
+        ];
+        let warnings = warnings("framework module Foo { umbrella header \"Foo.h\"\n header \"Listed.h\" }", &headers);
+        assert_eq!(warnings, [("header-not-in-umbrella".to_string(), "Public header 'Orphan.h' is not included by the umbrella header".to_string())]);
+    }
+
+    #[test]
+    fn warns_about_missing_umbrella_header() {
+        let codes: Vec<String> = warnings("framework module Foo { umbrella header \"Foo.h\" }", &[("Other.h", "")])
+            .into_iter()
+            .map(|(code, _)| code)
+            .collect();
+        assert_eq!(codes, ["missing-umbrella-header"]);
+    }
+}
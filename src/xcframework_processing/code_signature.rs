@@ -0,0 +1,838 @@
+use cms::cert::CertificateChoices;
+use cms::content_info::ContentInfo;
+use cms::signed_data::{SignedData, SignerIdentifier};
+use der::asn1::{ObjectIdentifier, OctetStringRef};
+use der::{Decode, Encode, Tag, Tagged};
+use goblin::mach::load_command::CommandVariant;
+use x509_cert::name::Name;
+use x509_cert::time::Time;
+use x509_cert::Certificate;
+use crate::framework_info::{CertificateType, SignatureInfo, Warning};
+use crate::xcframework_processing::archive_index::ArchiveIndex;
+use crate::xcframework_processing::archive_source::ArchiveSource;
+use crate::xcframework_processing::macho::{self, read_c_string, ArchitectureImage, MachOFile};
+use crate::xcframework_processing::signature_verification;
+
+const CSMAGIC_REQUIREMENT: u32 = 0xfade0c00;
+const CSMAGIC_REQUIREMENTS: u32 = 0xfade0c01;
+const CSMAGIC_CODEDIRECTORY: u32 = 0xfade0c02;
+const CSMAGIC_EMBEDDED_SIGNATURE: u32 = 0xfade0cc0;
+const CSMAGIC_BLOBWRAPPER: u32 = 0xfade0b01;
+
+/// `CS_ADHOC`, set in the CodeDirectory of ad-hoc signed code.
+const CS_ADHOC: u32 = 0x2;
+
+/// Directory holding the detached signature of a bundle.
+pub const SIGNATURE_DIRECTORY: &str = "_CodeSignature/";
+/// Seal of the bundle resources, stored next to the detached signature.
+pub const CODE_RESOURCES: &str = "CodeResources";
+
+const MESSAGE_DIGEST: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.4");
+const SIGNING_TIME: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.5");
+const COMMON_NAME: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.4.3");
+const ORGANIZATION: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.4.10");
+const ORGANIZATIONAL_UNIT: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.4.11");
+
+/// Parsed `CodeDirectory` blob, the part of a signature that seals the code.
+#[derive(Debug, Clone)]
+pub struct CodeDirectory {
+    pub version: u32,
+    pub flags: u32,
+    pub identifier: String,
+    pub team_id: Option<String>,
+    pub hash_type: u8,
+    /// Size of the pages hashed in `code_slots`, `0` when the code is hashed as a single page.
+    pub page_size: usize,
+    pub code_limit: u64,
+    /// `special_slots[i]` holds the hash of special slot `-(i + 1)` (Info.plist, requirements, resources...).
+    pub special_slots: Vec<Vec<u8>>,
+    pub code_slots: Vec<Vec<u8>>,
+    /// The blob itself, as hashed for the cdhash and signed by the CMS signature.
+    pub raw: Vec<u8>,
+}
+
+/// Blobs of a signature, read either from an embedded SuperBlob or from the
+/// detached files of a `_CodeSignature` directory.
+#[derive(Debug, Default)]
+pub struct SignatureBlobs {
+    pub code_directories: Vec<CodeDirectory>,
+    pub requirements: Vec<String>,
+    /// Requirement set blob, as hashed in special slot -2.
+    pub requirements_data: Option<Vec<u8>>,
+    /// DER encoded CMS `SignedData`, empty for ad-hoc signatures.
+    pub cms: Option<Vec<u8>>,
+}
+
+impl SignatureBlobs {
+    /// Returns the CodeDirectory with the strongest hash, the one `codesign` verifies first.
+    pub fn code_directory(&self) -> Option<&CodeDirectory> {
+        self.code_directories.iter().max_by_key(|directory| hash_strength(directory.hash_type))
+    }
+}
+
+/// Reports the signature of the xcframework itself, from its top-level `_CodeSignature`,
+/// and verifies it against the content of the archive.
+pub fn extract_framework_signature(
+    archive: &mut dyn ArchiveSource,
+    index: &ArchiveIndex,
+    warnings: &mut Vec<Warning>,
+) -> SignatureInfo {
+    let directory = index.root_path(SIGNATURE_DIRECTORY);
+    match read_detached(archive, index, &directory) {
+        Ok(Some(blobs)) => {
+            let base = index.root_path("");
+            let mut info = unwrap_signature(signature_info(&blobs), &directory, warnings);
+            let mut verification = signature_verification::verify_bundle(archive, index, &base, &blobs, None);
+            signature_verification::finish(&mut verification, &base, warnings);
+            info.verification = Some(verification);
+            info
+        }
+        Ok(None) => SignatureInfo::default(),
+        Err(e) => unwrap_signature(Err(e), &directory, warnings),
+    }
+}
+
+/// Reports the signature of a slice: the detached `_CodeSignature` of its bundle
+/// if there is one, otherwise the signature embedded in its binary.
+pub fn extract_library_signature(
+    archive: &mut dyn ArchiveSource,
+    index: &ArchiveIndex,
+    library_identifier: &str,
+    library_path: &str,
+    binary_name: &str,
+    images: &[ArchitectureImage],
+    warnings: &mut Vec<Warning>,
+) -> SignatureInfo {
+    let directory = library_signature_directory(index, library_identifier, library_path);
+    let base = match &directory {
+        Some(directory) => directory.trim_end_matches(SIGNATURE_DIRECTORY).to_string(),
+        None => format!("{}/", index.slice_path(library_identifier, library_path)),
+    };
+
+    // A `_CodeSignature/` holding only CodeResources seals the resources of an embedded signature.
+    let detached = match &directory {
+        Some(directory) => read_detached(archive, index, directory),
+        None => Ok(None),
+    };
+    match detached {
+        Ok(Some(blobs)) => {
+            let mut info = unwrap_signature(signature_info(&blobs), &base, warnings);
+            let mut verification = signature_verification::verify_bundle(archive, index, &base, &blobs, None);
+            signature_verification::finish(&mut verification, &base, warnings);
+            info.verification = Some(verification);
+            return info;
+        }
+        Ok(None) => {}
+        Err(e) => return unwrap_signature(Err(e), &base, warnings),
+    }
+
+    // Every architecture of a fat binary carries its own signature, made with the same identity.
+    let mut signed = Vec::new();
+    for image in images.iter().filter(|image| !image.is_archive) {
+        let Some(file) = image.objects.first() else {
+            continue;
+        };
+        match read_embedded(file) {
+            Ok(Some(blobs)) => signed.push((macho::architecture_name(image.cpu_type, image.cpu_subtype), file, blobs)),
+            Ok(None) => {}
+            Err(e) => return unwrap_signature(Err(e), binary_name, warnings),
+        }
+    }
+    let Some((_, _, blobs)) = signed.first() else {
+        return SignatureInfo::default();
+    };
+
+    let mut info = unwrap_signature(signature_info(blobs), binary_name, warnings);
+    let mut verification = signature_verification::verify_bundle(archive, index, &base, blobs, Some(binary_name));
+    let executable = binary_name.strip_prefix(&base).unwrap_or(binary_name);
+    for (architecture, file, blobs) in &signed {
+        signature_verification::verify_code(file, blobs, &format!("{} ({})", executable, architecture), &mut verification);
+    }
+    signature_verification::finish(&mut verification, binary_name, warnings);
+    info.verification = Some(verification);
+    info
+}
+
+fn unwrap_signature(result: Result<SignatureInfo, String>, path: &str, warnings: &mut Vec<Warning>) -> SignatureInfo {
+    result.unwrap_or_else(|e| {
+        warnings.push(Warning { code: "invalid-signature".to_string(), message: e, path: Some(path.to_string()) });
+        SignatureInfo::default()
+    })
+}
+
+/// Finds the `_CodeSignature/` directory of a slice bundle, which sits under
+/// `Versions/A` for macOS frameworks.
+pub fn library_signature_directory(index: &ArchiveIndex, library_identifier: &str, library_path: &str) -> Option<String> {
+    index
+        .library_files(library_identifier, library_path)
+        .filter_map(|file| file.name.find(SIGNATURE_DIRECTORY).map(|position| &file.name[..position + SIGNATURE_DIRECTORY.len()]))
+        .min_by_key(|directory| directory.len())
+        .map(|directory| directory.to_string())
+}
+
+/// Reads the detached signature files of a `_CodeSignature/` directory.
+///
+/// Each file holds a single blob; the alternate CodeDirectory is stored as
+/// `CodeRequirements-1`, so files are told apart by their magic, not their name.
+pub fn read_detached(archive: &mut dyn ArchiveSource, index: &ArchiveIndex, directory: &str) -> Result<Option<SignatureBlobs>, String> {
+    let names: Vec<String> = index
+        .files_under(directory)
+        .filter(|file| !file.name.ends_with(CODE_RESOURCES))
+        .map(|file| file.name.clone())
+        .collect();
+    if names.is_empty() {
+        return Ok(None);
+    }
+
+    let mut blobs = SignatureBlobs::default();
+    for name in names {
+        let bytes = archive.read_file(&name)?;
+        parse_blob(&bytes, &mut blobs).map_err(|e| format!("Failed to parse {}: {}", name, e))?;
+    }
+    if blobs.code_directories.is_empty() {
+        return Err(format!("No CodeDirectory found in {}", directory));
+    }
+    Ok(Some(blobs))
+}
+
+/// Reads the signature SuperBlob pointed to by `LC_CODE_SIGNATURE`.
+pub fn read_embedded(file: &MachOFile) -> Result<Option<SignatureBlobs>, String> {
+    let Some((offset, size)) = file.macho.load_commands.iter().find_map(|command| match command.command {
+        CommandVariant::CodeSignature(data) => Some((data.dataoff as usize, data.datasize as usize)),
+        _ => None,
+    }) else {
+        return Ok(None);
+    };
+    let bytes = file
+        .bytes
+        .get(offset..offset + size)
+        .ok_or_else(|| "Failed to read code signature: LC_CODE_SIGNATURE points outside of the binary".to_string())?;
+
+    let mut blobs = SignatureBlobs::default();
+    parse_blob(bytes, &mut blobs).map_err(|e| format!("Failed to parse code signature: {}", e))?;
+    Ok(Some(blobs))
+}
+
+fn parse_blob(bytes: &[u8], blobs: &mut SignatureBlobs) -> Result<(), String> {
+    let magic = read_u32(bytes, 0).ok_or("truncated blob")?;
+    let length = read_u32(bytes, 4).ok_or("truncated blob")? as usize;
+    let blob = bytes.get(..length).ok_or("blob length exceeds its data")?;
+
+    match magic {
+        CSMAGIC_EMBEDDED_SIGNATURE => {
+            let count = read_u32(blob, 8).ok_or("truncated SuperBlob")? as usize;
+            let index_end = 12 + count * 8;
+            for slot in 0..count {
+                let offset = read_u32(blob, 12 + slot * 8 + 4).ok_or("truncated SuperBlob index")? as usize;
+                // Entries pointing back into the header or at another SuperBlob would be parsed forever.
+                if offset < index_end {
+                    return Err("SuperBlob index points inside its header".to_string());
+                }
+                let entry = blob.get(offset..).ok_or("SuperBlob index points outside of the blob")?;
+                if read_u32(entry, 0) == Some(CSMAGIC_EMBEDDED_SIGNATURE) {
+                    return Err("nested SuperBlob".to_string());
+                }
+                parse_blob(entry, blobs)?;
+            }
+        }
+        CSMAGIC_CODEDIRECTORY => blobs.code_directories.push(parse_code_directory(blob)?),
+        CSMAGIC_REQUIREMENTS => {
+            blobs.requirements = parse_requirements(blob)?;
+            blobs.requirements_data = Some(blob.to_vec());
+        }
+        CSMAGIC_BLOBWRAPPER => blobs.cms = Some(blob.get(8..).ok_or("truncated blob wrapper")?.to_vec()),
+        // Entitlements, launch constraints and unknown blobs are not reported.
+        _ => {}
+    }
+    Ok(())
+}
+
+fn parse_code_directory(blob: &[u8]) -> Result<CodeDirectory, String> {
+    let field = |offset: usize| read_u32(blob, offset).ok_or_else(|| "truncated CodeDirectory".to_string());
+    let version = field(8)?;
+    let flags = field(12)?;
+    let hash_offset = field(16)? as usize;
+    let ident_offset = field(20)? as usize;
+    let special_count = field(24)? as usize;
+    let code_count = field(28)? as usize;
+    let header = blob.get(36..40).ok_or("truncated CodeDirectory")?;
+    let (hash_size, hash_type, page_shift) = (header[0] as usize, header[1], header[3]);
+
+    let team_offset = if version >= 0x20200 { field(48)? as usize } else { 0 };
+    let code_limit = match version >= 0x20300 {
+        true => read_u64(blob, 56).filter(|&limit| limit != 0).unwrap_or(field(32)? as u64),
+        false => field(32)? as u64,
+    };
+
+    // The slot counts are untrusted, so the hashes must fit in the blob before any is read.
+    if hash_size == 0 {
+        return Err("invalid CodeDirectory hash size".to_string());
+    }
+    let code_end = code_count.checked_mul(hash_size).and_then(|size| size.checked_add(hash_offset));
+    let special_size = special_count.checked_mul(hash_size);
+    if code_end.is_none_or(|end| end > blob.len()) || special_size.is_none_or(|size| size > hash_offset) {
+        return Err("CodeDirectory hash slot outside of the blob".to_string());
+    }
+    let page_size = match page_shift {
+        0 => 0,
+        shift => 1usize.checked_shl(shift.into()).ok_or("invalid CodeDirectory page size")?,
+    };
+
+    let hash = |offset: usize| {
+        blob.get(offset..offset + hash_size)
+            .map(|hash| hash.to_vec())
+            .ok_or_else(|| "CodeDirectory hash slot outside of the blob".to_string())
+    };
+    let special_slots = (1..=special_count)
+        .map(|slot| hash_offset.checked_sub(slot * hash_size).ok_or("CodeDirectory hash slot outside of the blob".to_string()).and_then(hash))
+        .collect::<Result<Vec<_>, String>>()?;
+    let code_slots = (0..code_count).map(|slot| hash(hash_offset + slot * hash_size)).collect::<Result<Vec<_>, String>>()?;
+
+    Ok(CodeDirectory {
+        version,
+        flags,
+        identifier: read_c_string(blob, ident_offset).ok_or("CodeDirectory identifier outside of the blob")?,
+        team_id: (team_offset != 0).then(|| read_c_string(blob, team_offset)).flatten(),
+        hash_type,
+        page_size,
+        code_limit,
+        special_slots,
+        code_slots,
+        raw: blob.to_vec(),
+    })
+}
+
+/// Decompiles the requirement set into the `csreq` text form (e.g. `designated => identifier "..." and anchor apple generic`).
+fn parse_requirements(blob: &[u8]) -> Result<Vec<String>, String> {
+    let count = read_u32(blob, 8).ok_or("truncated requirement set")? as usize;
+    let mut requirements = Vec::with_capacity(count);
+    for slot in 0..count {
+        let kind = read_u32(blob, 12 + slot * 8).ok_or("truncated requirement set")?;
+        let offset = read_u32(blob, 16 + slot * 8).ok_or("truncated requirement set")? as usize;
+        let name = match kind {
+            1 => "host",
+            2 => "guest",
+            3 => "designated",
+            4 => "library",
+            5 => "plugin",
+            _ => "unknown",
+        };
+
+        let requirement = blob.get(offset..).ok_or("requirement outside of the requirement set")?;
+        if read_u32(requirement, 0) != Some(CSMAGIC_REQUIREMENT) {
+            return Err("invalid requirement magic".to_string());
+        }
+        let length = (read_u32(requirement, 4).unwrap_or(0) as usize).min(requirement.len());
+        let mut reader = Reader { bytes: &requirement[..length], position: 12 };
+        match expression(&mut reader) {
+            Some((text, _)) => requirements.push(format!("{} => {}", name, text)),
+            // Unknown opcodes only hide the expression, not the requirement itself.
+            None => requirements.push(name.to_string()),
+        }
+    }
+    Ok(requirements)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn u32(&mut self) -> Option<u32> {
+        let value = read_u32(self.bytes, self.position)?;
+        self.position += 4;
+        Some(value)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        let value = read_u64(self.bytes, self.position)?;
+        self.position += 8;
+        Some(value)
+    }
+
+    /// Reads length-prefixed data, padded to a multiple of four bytes.
+    fn data(&mut self) -> Option<&'a [u8]> {
+        let length = self.u32()? as usize;
+        let data = self.bytes.get(self.position..self.position + length)?;
+        self.position += length.div_ceil(4) * 4;
+        Some(data)
+    }
+
+    fn string(&mut self) -> Option<String> {
+        Some(format!("\"{}\"", String::from_utf8_lossy(self.data()?)))
+    }
+
+    fn slot(&mut self) -> Option<String> {
+        Some(match self.u32()? as i32 {
+            0 => "leaf".to_string(),
+            -1 => "root".to_string(),
+            slot => slot.to_string(),
+        })
+    }
+}
+
+/// Precedence of `or`, `and` and atoms, used to parenthesize nested expressions.
+const PRECEDENCE_OR: u8 = 0;
+const PRECEDENCE_AND: u8 = 1;
+const PRECEDENCE_ATOM: u8 = 2;
+
+fn expression(reader: &mut Reader) -> Option<(String, u8)> {
+    // The high byte carries flags that do not change the meaning of the operation.
+    let op = reader.u32()? & 0x00ff_ffff;
+    let atom = |text: String| Some((text, PRECEDENCE_ATOM));
+    match op {
+        0 => atom("never".to_string()),
+        1 => atom("always".to_string()),
+        2 => atom(format!("identifier {}", reader.string()?)),
+        3 => atom("anchor apple".to_string()),
+        4 => {
+            let slot = reader.slot()?;
+            atom(format!("certificate {} = H\"{}\"", slot, hex(reader.data()?)))
+        }
+        5 => {
+            let key = reader.string()?;
+            atom(format!("info[{}] = {}", key.trim_matches('"'), reader.string()?))
+        }
+        6 | 7 => {
+            let (precedence, keyword) = if op == 6 { (PRECEDENCE_AND, "and") } else { (PRECEDENCE_OR, "or") };
+            let left = parenthesize(expression(reader)?, precedence);
+            let right = parenthesize(expression(reader)?, precedence);
+            Some((format!("{} {} {}", left, keyword, right), precedence))
+        }
+        8 => atom(format!("cdhash H\"{}\"", hex(reader.data()?))),
+        9 => atom(format!("! {}", parenthesize(expression(reader)?, PRECEDENCE_ATOM))),
+        10 => {
+            let key = reader.string()?;
+            atom(format!("info[{}] {}", key.trim_matches('"'), match_expression(reader)?))
+        }
+        11 => {
+            let slot = reader.slot()?;
+            let field = reader.string()?;
+            atom(format!("certificate {}[{}] {}", slot, field.trim_matches('"'), match_expression(reader)?))
+        }
+        12 => atom(format!("certificate {} trusted", reader.slot()?)),
+        13 => atom("anchor trusted".to_string()),
+        14 | 17 | 22 => {
+            let slot = reader.slot()?;
+            let oid = oid(reader.data()?)?;
+            let prefix = match op {
+                14 => "field",
+                17 => "policy",
+                _ => "timestamp",
+            };
+            atom(format!("certificate {}[{}.{}] {}", slot, prefix, oid, match_expression(reader)?))
+        }
+        15 => atom("anchor apple generic".to_string()),
+        16 => {
+            let key = reader.string()?;
+            atom(format!("entitlement[{}] {}", key.trim_matches('"'), match_expression(reader)?))
+        }
+        18 => atom(format!("anchor apple {}", reader.string()?)),
+        19 => atom(reader.string()?),
+        20 => atom(format!("platform = {}", reader.u32()?)),
+        21 => atom("notarized".to_string()),
+        23 => atom("legacy".to_string()),
+        _ => None,
+    }
+}
+
+fn parenthesize((text, precedence): (String, u8), parent: u8) -> String {
+    if precedence < parent {
+        format!("({})", text)
+    } else {
+        text
+    }
+}
+
+fn match_expression(reader: &mut Reader) -> Option<String> {
+    let op = reader.u32()?;
+    Some(match op {
+        0 => "exists".to_string(),
+        1 => format!("= {}", reader.string()?),
+        2 => format!("~ {}", reader.string()?),
+        3 => format!("= {}*\"", reader.string()?.trim_end_matches('"')),
+        4 => format!("= \"*{}", reader.string()?.trim_start_matches('"')),
+        5 => format!("< {}", reader.string()?),
+        6 => format!("> {}", reader.string()?),
+        7 => format!("<= {}", reader.string()?),
+        8 => format!(">= {}", reader.string()?),
+        9 => format!("= timestamp \"{}\"", reader.u64()?),
+        10 => format!("< timestamp \"{}\"", reader.u64()?),
+        11 => format!("> timestamp \"{}\"", reader.u64()?),
+        12 => format!("<= timestamp \"{}\"", reader.u64()?),
+        13 => format!(">= timestamp \"{}\"", reader.u64()?),
+        14 => "absent".to_string(),
+        _ => return None,
+    })
+}
+
+/// Formats the DER content of an object identifier in dotted notation.
+fn oid(bytes: &[u8]) -> Option<String> {
+    let (first, rest) = bytes.split_first()?;
+    let mut arcs = vec![(first / 40).to_string(), (first % 40).to_string()];
+    let mut value: u64 = 0;
+    for byte in rest {
+        value = (value << 7) | (byte & 0x7f) as u64;
+        if byte & 0x80 == 0 {
+            arcs.push(value.to_string());
+            value = 0;
+        }
+    }
+    Some(arcs.join("."))
+}
+
+/// Builds the report of a signature from its blobs.
+pub fn signature_info(blobs: &SignatureBlobs) -> Result<SignatureInfo, String> {
+    let Some(code_directory) = blobs.code_directory() else {
+        return Ok(SignatureInfo::default());
+    };
+
+    let mut info = SignatureInfo {
+        signed: true,
+        identifier: Some(code_directory.identifier.clone()),
+        team_id: code_directory.team_id.clone(),
+        hash_types: blobs.code_directories.iter().map(|directory| hash_type_name(directory.hash_type)).collect(),
+        requirements: blobs.requirements.clone(),
+        ..SignatureInfo::default()
+    };
+
+    // Ad-hoc signatures carry an empty CMS blob, or none at all.
+    let cms = blobs.cms.as_deref().filter(|cms| !cms.is_empty());
+    let Some(cms) = cms.filter(|_| code_directory.flags & CS_ADHOC == 0) else {
+        info.certificate_type = Some(CertificateType::AdHoc);
+        return Ok(info);
+    };
+
+    let signed_data = parse_signed_data(cms)?;
+
+    let certificates: Vec<&Certificate> = signed_data
+        .certificates
+        .iter()
+        .flat_map(|set| set.0.iter())
+        .filter_map(|choice| match choice {
+            CertificateChoices::Certificate(certificate) => Some(certificate),
+            _ => None,
+        })
+        .collect();
+    let signer = signed_data.signer_infos.0.iter().next();
+    let leaf = signer
+        .and_then(|signer| match &signer.sid {
+            SignerIdentifier::IssuerAndSerialNumber(id) => certificates.iter().find(|certificate| {
+                certificate.tbs_certificate.issuer == id.issuer && certificate.tbs_certificate.serial_number == id.serial_number
+            }),
+            SignerIdentifier::SubjectKeyIdentifier(_) => None,
+        })
+        .or(certificates.first());
+
+    if let Some(leaf) = leaf {
+        let subject = &leaf.tbs_certificate.subject;
+        let issuer = &leaf.tbs_certificate.issuer;
+        info.identity = name_attribute(subject, COMMON_NAME);
+        info.team_id = name_attribute(subject, ORGANIZATIONAL_UNIT).or(info.team_id);
+        info.certificate_type = Some(certificate_type(info.identity.as_deref(), subject, issuer));
+    }
+
+    info.signing_time = signer
+        .and_then(|signer| signer.signed_attrs.as_ref())
+        .and_then(|attributes| attributes.iter().find(|attribute| attribute.oid == SIGNING_TIME))
+        .and_then(|attribute| attribute.values.iter().next())
+        .and_then(|value| Time::from_der(&value.to_der().ok()?).ok())
+        .map(|time| time.to_date_time().to_string());
+
+    Ok(info)
+}
+
+/// Returns the digest algorithm and the `messageDigest` signed attribute of a CMS
+/// signature, i.e. what the signer claims the hash of the CodeDirectory to be.
+pub fn message_digest(cms: &[u8]) -> Result<Option<(ObjectIdentifier, Vec<u8>)>, String> {
+    let signed_data = parse_signed_data(cms)?;
+    let Some(signer) = signed_data.signer_infos.0.iter().next() else {
+        return Ok(None);
+    };
+    let digest = signer
+        .signed_attrs
+        .as_ref()
+        .and_then(|attributes| attributes.iter().find(|attribute| attribute.oid == MESSAGE_DIGEST))
+        .and_then(|attribute| attribute.values.iter().next())
+        .and_then(|value| value.decode_as::<OctetStringRef>().ok())
+        .map(|digest| digest.as_bytes().to_vec());
+    Ok(digest.map(|digest| (signer.digest_alg.oid, digest)))
+}
+
+fn parse_signed_data(cms: &[u8]) -> Result<SignedData, String> {
+    let der = ber_to_der(cms).ok_or_else(|| "Failed to parse CMS signature: malformed BER".to_string())?;
+    let content_info = ContentInfo::from_der(&der).map_err(|e| format!("Failed to parse CMS signature: {}", e))?;
+    content_info
+        .content
+        .decode_as()
+        .map_err(|e| format!("Failed to parse CMS signed data: {}", e))
+}
+
+/// Classifies the signing certificate from its common name. Only certificates
+/// issued by Apple count as Apple Development or Distribution.
+fn certificate_type(common_name: Option<&str>, subject: &Name, issuer: &Name) -> CertificateType {
+    if subject == issuer {
+        return CertificateType::SelfSigned;
+    }
+    if name_attribute(issuer, ORGANIZATION).as_deref() != Some("Apple Inc.") {
+        return CertificateType::Other;
+    }
+
+    let common_name = common_name.unwrap_or_default();
+    let starts_with = |prefixes: &[&str]| prefixes.iter().any(|prefix| common_name.starts_with(prefix));
+    if starts_with(&["Apple Development:", "iPhone Developer:", "Mac Developer:"]) {
+        CertificateType::AppleDevelopment
+    } else if starts_with(&["Apple Distribution:", "iPhone Distribution:", "3rd Party Mac Developer Application:"]) {
+        CertificateType::AppleDistribution
+    } else if starts_with(&["Developer ID Application:"]) {
+        CertificateType::DeveloperId
+    } else {
+        CertificateType::Other
+    }
+}
+
+fn name_attribute(name: &Name, oid: ObjectIdentifier) -> Option<String> {
+    name.0
+        .iter()
+        .flat_map(|rdn| rdn.0.iter())
+        .find(|attribute| attribute.oid == oid)
+        .filter(|attribute| {
+            matches!(attribute.value.tag(), Tag::Utf8String | Tag::PrintableString | Tag::Ia5String | Tag::TeletexString)
+        })
+        .and_then(|attribute| std::str::from_utf8(attribute.value.value()).ok())
+        .map(|value| value.to_string())
+}
+
+/// Rewrites the indefinite lengths and constructed strings `codesign` uses for the
+/// outer layers of its CMS blob as their DER form, so that the result can be decoded.
+fn ber_to_der(bytes: &[u8]) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(bytes.len());
+    let mut position = 0;
+    while position < bytes.len() {
+        // Some blobs are zero padded after the CMS structure.
+        if bytes[position..].iter().all(|&byte| byte == 0) {
+            break;
+        }
+        position += normalize_element(&bytes[position..], &mut output, 0)?;
+    }
+    Some(output)
+}
+
+/// Deepest nesting of constructed elements accepted, to bound the recursion on hostile input.
+const MAX_BER_DEPTH: usize = 32;
+
+fn normalize_element(bytes: &[u8], output: &mut Vec<u8>, depth: usize) -> Option<usize> {
+    if depth > MAX_BER_DEPTH {
+        return None;
+    }
+    let mut tag_length = 1;
+    if bytes.first()? & 0x1f == 0x1f {
+        while bytes.get(tag_length)? & 0x80 != 0 {
+            tag_length += 1;
+        }
+        tag_length += 1;
+    }
+    let constructed = bytes[0] & 0x20 != 0;
+    let first = *bytes.get(tag_length)?;
+    let mut position = tag_length + 1;
+    let mut content = Vec::new();
+
+    if first == 0x80 {
+        if !constructed {
+            return None;
+        }
+        while bytes.get(position..position + 2)? != [0, 0] {
+            position += normalize_element(&bytes[position..], &mut content, depth + 1)?;
+        }
+        position += 2;
+    } else {
+        let length = if first & 0x80 == 0 {
+            first as usize
+        } else {
+            let count = (first & 0x7f) as usize;
+            let length = long_form_length(bytes.get(position..position + count)?)?;
+            position += count;
+            length
+        };
+        let body = bytes.get(position..position.checked_add(length)?)?;
+        position += length;
+        if constructed {
+            let mut offset = 0;
+            while offset < body.len() {
+                offset += normalize_element(&body[offset..], &mut content, depth + 1)?;
+            }
+        } else {
+            content.extend_from_slice(body);
+        }
+    }
+
+    // Constructed universal strings (e.g. a chunked OCTET STRING) become primitive ones.
+    let mut tag = bytes[..tag_length].to_vec();
+    if constructed && bytes[0] & 0xc0 == 0 && !matches!(bytes[0] & 0x1f, 0x10 | 0x11) {
+        content = flatten_string(&content)?;
+        tag[0] &= !0x20;
+    }
+
+    output.extend_from_slice(&tag);
+    if content.len() < 0x80 {
+        output.push(content.len() as u8);
+    } else {
+        let length = content.len().to_be_bytes();
+        let skip = length.iter().take_while(|&&byte| byte == 0).count();
+        output.push(0x80 | (length.len() - skip) as u8);
+        output.extend_from_slice(&length[skip..]);
+    }
+    output.extend_from_slice(&content);
+    Some(position)
+}
+
+/// Concatenates the (already normalized) chunks of a constructed string.
+fn flatten_string(mut chunks: &[u8]) -> Option<Vec<u8>> {
+    let mut value = Vec::new();
+    while let Some(&first) = chunks.get(1) {
+        let (length, header) = match first {
+            length if length < 0x80 => (length as usize, 2),
+            long => {
+                let count = (long & 0x7f) as usize;
+                (long_form_length(chunks.get(2..2 + count)?)?, 2 + count)
+            }
+        };
+        let end = header.checked_add(length)?;
+        value.extend_from_slice(chunks.get(header..end)?);
+        chunks = &chunks[end..];
+    }
+    Some(value)
+}
+
+/// Folds the bytes of a long-form BER length, failing when it does not fit in a `usize`.
+fn long_form_length(bytes: &[u8]) -> Option<usize> {
+    bytes.iter().try_fold(0usize, |length, &byte| length.checked_mul(256)?.checked_add(byte as usize))
+}
+
+/// Returns the name of a CodeDirectory hash type (`CS_HASHTYPE_*`).
+pub fn hash_type_name(hash_type: u8) -> String {
+    match hash_type {
+        1 => "sha1".to_string(),
+        2 => "sha256".to_string(),
+        3 => "sha256_truncated".to_string(),
+        4 => "sha384".to_string(),
+        other => format!("unknown({})", other),
+    }
+}
+
+fn hash_strength(hash_type: u8) -> u8 {
+    match hash_type {
+        4 => 3,
+        2 => 2,
+        3 => 1,
+        _ => 0,
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Code signing blobs are big-endian, unlike the Mach-O they are embedded in.
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    bytes.get(offset..offset + 4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Option<u64> {
+    bytes.get(offset..offset + 8).map(|b| u64::from_be_bytes(b.try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(values: &[u32]) -> Vec<u8> {
+        values.iter().flat_map(|value| value.to_be_bytes()).collect()
+    }
+
+    #[test]
+    fn rejects_super_blob_index_pointing_at_its_header() {
+        let bytes = words(&[CSMAGIC_EMBEDDED_SIGNATURE, 20, 1, 0, 0]);
+        let result = parse_blob(&bytes, &mut SignatureBlobs::default());
+        assert_eq!(result.unwrap_err(), "SuperBlob index points inside its header");
+    }
+
+    #[test]
+    fn rejects_nested_super_blob() {
+        let mut bytes = words(&[CSMAGIC_EMBEDDED_SIGNATURE, 32, 1, 0, 20]);
+        bytes.extend(words(&[CSMAGIC_EMBEDDED_SIGNATURE, 12, 0]));
+        let result = parse_blob(&bytes, &mut SignatureBlobs::default());
+        assert_eq!(result.unwrap_err(), "nested SuperBlob");
+    }
+
+    #[test]
+    fn rejects_short_blob_wrapper() {
+        let bytes = words(&[CSMAGIC_BLOBWRAPPER, 4]);
+        let result = parse_blob(&bytes, &mut SignatureBlobs::default());
+        assert_eq!(result.unwrap_err(), "truncated blob wrapper");
+    }
+
+    /// CodeDirectory with the identifier `x` and `code_count` hashes of `hash_size` bytes.
+    fn code_directory(hash_size: u8, page_shift: u8, code_count: u32) -> Vec<u8> {
+        let slots = code_count.min(2) as usize * hash_size as usize;
+        let length = 48 + slots as u32;
+        let mut bytes = words(&[CSMAGIC_CODEDIRECTORY, length, 0x20001, 0, 48, 44, 0, code_count, 4096]);
+        bytes.extend([hash_size, 2, 0, page_shift]);
+        bytes.extend(words(&[0]));
+        bytes.extend(b"x\0\0\0");
+        bytes.extend(vec![0xab; slots]);
+        bytes
+    }
+
+    #[test]
+    fn parses_code_directory() {
+        let directory = parse_code_directory(&code_directory(32, 12, 2)).unwrap();
+        assert_eq!(directory.identifier, "x");
+        assert_eq!(directory.page_size, 4096);
+        assert_eq!(directory.code_slots, vec![vec![0xab; 32]; 2]);
+    }
+
+    #[test]
+    fn rejects_oversized_page_shift() {
+        let result = parse_code_directory(&code_directory(32, 64, 1));
+        assert_eq!(result.unwrap_err(), "invalid CodeDirectory page size");
+    }
+
+    #[test]
+    fn rejects_empty_hashes() {
+        let result = parse_code_directory(&code_directory(0, 12, u32::MAX));
+        assert_eq!(result.unwrap_err(), "invalid CodeDirectory hash size");
+    }
+
+    #[test]
+    fn rejects_hash_slots_outside_of_the_blob() {
+        let result = parse_code_directory(&code_directory(32, 12, u32::MAX));
+        assert_eq!(result.unwrap_err(), "CodeDirectory hash slot outside of the blob");
+    }
+
+    #[test]
+    fn normalizes_indefinite_lengths() {
+        // SEQUENCE (indefinite) { OCTET STRING (constructed, indefinite) { "ab", "c" } }
+        let ber = [0x30, 0x80, 0x24, 0x80, 0x04, 0x02, b'a', b'b', 0x04, 0x01, b'c', 0, 0, 0, 0];
+        assert_eq!(ber_to_der(&ber).unwrap(), vec![0x30, 0x05, 0x04, 0x03, b'a', b'b', b'c']);
+    }
+
+    #[test]
+    fn rejects_overflowing_long_form_length() {
+        let ber = [0x04, 0x88, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+        assert_eq!(ber_to_der(&ber), None);
+        let ber = [0x04, 0x89, 0x01, 0, 0, 0, 0, 0, 0, 0, 0, 0x00];
+        assert_eq!(ber_to_der(&ber), None);
+    }
+
+    #[test]
+    fn rejects_deeply_nested_elements() {
+        let mut ber = [0x30, 0x80].repeat(MAX_BER_DEPTH + 2);
+        ber.extend([0u8; 2].repeat(MAX_BER_DEPTH + 2));
+        assert_eq!(ber_to_der(&ber), None);
+        let mut ber = [0x30, 0x80].repeat(MAX_BER_DEPTH);
+        ber.extend([0u8; 2].repeat(MAX_BER_DEPTH));
+        assert!(ber_to_der(&ber).is_some());
+    }
+}
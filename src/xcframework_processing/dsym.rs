@@ -0,0 +1,140 @@
+use std::path::Path;
+use crate::framework_info::{DsymInfo, DsymStatus, LibraryInfo, Warning};
+use crate::xcframework_processing::archive_index::{ArchiveIndex, FileKind};
+use crate::xcframework_processing::archive_source::{ArchiveSource, DirectorySource};
+use crate::xcframework_processing::macho;
+
+/// Path of the DWARF binaries inside a `.dSYM` bundle.
+const DWARF_DIRECTORY: &str = ".dSYM/Contents/Resources/DWARF/";
+
+/// A DWARF binary found in a dSYM bundle, with the UUID of each of its architectures.
+pub struct DsymBinary {
+    /// Path inside the archive, or on disk for dSYMs read from `--dsym-dir`.
+    pub path: String,
+    /// Slice the dSYM was shipped in, `None` when it is not inside a slice.
+    pub slice: Option<String>,
+    /// File name of the DWARF binary, which matches the name of the binary it describes.
+    pub file_name: String,
+    /// `(architecture, uuid)` pairs.
+    pub uuids: Vec<(String, String)>,
+}
+
+/// Finds the dSYM bundles shipped in the archive and, if given, in `dsym_dir`.
+///
+/// `library_identifiers` are the slices of the xcframework, used to tell dSYMs
+/// shipped next to a slice apart from those shipped at the top level. Fails when
+/// `dsym_dir` cannot be read.
+pub fn collect_dsyms(
+    archive: &mut dyn ArchiveSource,
+    index: &ArchiveIndex,
+    library_identifiers: &[String],
+    dsym_dir: Option<&Path>,
+) -> Result<Vec<DsymBinary>, String> {
+    let mut dsyms = Vec::new();
+
+    let names: Vec<(String, Option<String>)> = index
+        .files_of_kind(FileKind::Other)
+        .filter(|file| file.name.contains(DWARF_DIRECTORY))
+        .map(|file| (file.name.clone(), file.slice.clone().filter(|slice| library_identifiers.contains(slice))))
+        .collect();
+    for (name, slice) in names {
+        if let Ok(bytes) = archive.read_file(&name) {
+            if let Some(dsym) = read_dsym(name, slice, &bytes) {
+                dsyms.push(dsym);
+            }
+        }
+    }
+
+    if let Some(directory) = dsym_dir {
+        if !directory.is_dir() {
+            return Err(format!("Failed to read the dSYM directory: {} is not a directory", directory.display()));
+        }
+        let mut source = DirectorySource::new(directory).map_err(|e| format!("Failed to read the dSYM directory: {}", e))?;
+        let base = directory.parent().unwrap_or(directory);
+        for entry in source.entries().map_err(|e| format!("Failed to read the dSYM directory: {}", e))? {
+            if entry.name.contains(DWARF_DIRECTORY) {
+                if let Ok(bytes) = source.read_file(&entry.name) {
+                    let path = base.join(&entry.name).display().to_string();
+                    if let Some(dsym) = read_dsym(path, None, &bytes) {
+                        dsyms.push(dsym);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(dsyms)
+}
+
+fn read_dsym(path: String, slice: Option<String>, bytes: &[u8]) -> Option<DsymBinary> {
+    let images = macho::parse_images(bytes).ok()?;
+    let uuids = images
+        .iter()
+        .filter_map(|image| {
+            let uuid = macho::uuid(image.objects.first()?)?;
+            Some((macho::architecture_name(image.cpu_type, image.cpu_subtype), uuid))
+        })
+        .collect();
+    let file_name = path.rsplit('/').next().unwrap_or_default().to_string();
+    Some(DsymBinary { path, slice, file_name, uuids })
+}
+
+/// Records, for every architecture of every slice binary, whether a dSYM with the same
+/// UUID is available. A dSYM for the same binary and architecture with another UUID is
+/// stale, unless it belongs to another slice of the framework.
+pub fn match_dsyms(libraries: &mut [LibraryInfo], index: &ArchiveIndex, dsyms: &[DsymBinary]) {
+    let binary_uuids: Vec<String> = libraries
+        .iter()
+        .flat_map(|library| library.architectures.iter().filter_map(|architecture| architecture.uuid.clone()))
+        .collect();
+
+    for library in libraries.iter_mut() {
+        let binary_file_name = library.binary_path.rsplit('/').next().unwrap_or_default().to_string();
+        // dSYMs of other slices describe other builds, even when they share an architecture name.
+        let candidates: Vec<&DsymBinary> = dsyms
+            .iter()
+            .filter(|dsym| dsym.slice.as_deref().is_none_or(|slice| slice == library.library_identifier))
+            .collect();
+
+        for architecture in library.architectures.iter_mut() {
+            let Some(uuid) = &architecture.uuid else {
+                continue;
+            };
+
+            let matching = candidates
+                .iter()
+                .find(|dsym| dsym.uuids.iter().any(|(_, dsym_uuid)| dsym_uuid == uuid));
+            if let Some(dsym) = matching {
+                architecture.dsym = Some(DsymInfo { status: DsymStatus::Present, path: Some(dsym.path.clone()), uuid: None });
+                continue;
+            }
+
+            let stale = candidates.iter().find_map(|dsym| {
+                if dsym.file_name != binary_file_name {
+                    return None;
+                }
+                dsym.uuids
+                    .iter()
+                    .find(|(name, dsym_uuid)| name == &architecture.name && !binary_uuids.contains(dsym_uuid))
+                    .map(|(_, dsym_uuid)| (dsym, dsym_uuid))
+            });
+            if let Some((dsym, dsym_uuid)) = stale {
+                library.warnings.push(Warning {
+                    code: "dsym-mismatch".to_string(),
+                    message: format!(
+                        "dSYM '{}' has UUID {} for {} but the binary has UUID {}",
+                        dsym.path, dsym_uuid, architecture.name, uuid
+                    ),
+                    path: Some(index.slice_path(&library.library_identifier, &library.binary_path)),
+                });
+                architecture.dsym = Some(DsymInfo {
+                    status: DsymStatus::Mismatched,
+                    path: Some(dsym.path.clone()),
+                    uuid: Some(dsym_uuid.clone()),
+                });
+            } else {
+                architecture.dsym = Some(DsymInfo { status: DsymStatus::Missing, path: None, uuid: None });
+            }
+        }
+    }
+}
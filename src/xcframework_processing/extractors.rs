@@ -1,31 +1,53 @@
-use std::fs::File;
-use std::io::{Cursor, Read};
-use plist::Value;
-use zip::ZipArchive;
-use crate::framework_info::{LibraryInfo, PrivacyInfo};
-use crate::utils::{calculate_library_size, ensure_unique_ordered};
+use std::io::Cursor;
+use plist::{Dictionary, Value};
+use crate::framework_info::{ArchitectureInfo, Embedding, LinkedLibrary, Linkage, LibraryInfo, PrivacyInfo, PrivacyManifest, Warning};
+use crate::utils::{calculate_library_size, compare_versions, ensure_unique_ordered, format_size};
 use crate::framework_info::{AccessedAPIType, CollectedDataType};
+use crate::xcframework_processing::archive_index::{ArchiveIndex, FileKind};
+use crate::xcframework_processing::archive_source::ArchiveSource;
+use crate::xcframework_processing::clang_module;
+use crate::xcframework_processing::code_signature;
+use crate::xcframework_processing::macho::{self, ArchitectureImage};
+use crate::xcframework_processing::privacy_manifest;
+use crate::xcframework_processing::required_reason;
+use crate::xcframework_processing::swift_details;
 
-pub fn extract_privacy_info(
-    archive: &mut ZipArchive<File>,
+/// Reads every privacy manifest of a slice: the library's own first, then those of the
+/// resource bundles shipped inside or next to it, outermost first.
+pub fn extract_privacy_manifests(
+    archive: &mut dyn ArchiveSource,
+    index: &ArchiveIndex,
     library_identifier: &str,
-    library_path: &str
-) -> Result<PrivacyInfo, String> {
-    let mut privacy_info_buffer = None;
-    let modules_path = format!("{}/{}", library_identifier, library_path);
-
-    for i in 0..archive.len() {
-        let mut file = archive
-            .by_index(i)
-            .map_err(|e| format!("Failed to access file in archive: {}", e))?;
-        if file.name().contains(&modules_path) && file.name().ends_with("PrivacyInfo.xcprivacy") {
-            let mut buffer = Vec::new();
-            file.read_to_end(&mut buffer).map_err(|e| format!("Failed to read file content: {}", e))?;
-            privacy_info_buffer = Some(buffer);
-            break;
+    warnings: &mut Vec<Warning>
+) -> Vec<PrivacyManifest> {
+    let mut paths: Vec<String> = index
+        .slice_files(library_identifier)
+        .filter(|file| file.kind == FileKind::PrivacyManifest)
+        .map(|file| file.name.clone())
+        .collect();
+    paths.sort_by_key(|path| (path.contains(".bundle/"), path.matches('/').count(), path.clone()));
+
+    let mut manifests = Vec::new();
+    for path in paths {
+        match archive.read_file(&path).and_then(|buffer| parse_privacy_info(buffer, &path, warnings)) {
+            Ok(privacy_info) => {
+                let bundle = path
+                    .split('/')
+                    .rfind(|component| component.ends_with(".bundle"))
+                    .map(|component| component.to_string());
+                manifests.push(PrivacyManifest { path, bundle, privacy_info });
+            }
+            Err(e) => warnings.push(Warning {
+                code: "invalid-privacy-manifest".to_string(),
+                message: e,
+                path: Some(path),
+            }),
         }
     }
+    manifests
+}
 
+fn parse_privacy_info(buffer: Vec<u8>, path: &str, warnings: &mut Vec<Warning>) -> Result<PrivacyInfo, String> {
     let mut privacy_info = PrivacyInfo {
         present: false,
         tracking: None,
@@ -34,51 +56,50 @@ pub fn extract_privacy_info(
         accessed_api_types: None,
     };
 
-    if let Some(buffer) = privacy_info_buffer {
-        let cursor = std::io::Cursor::new(buffer);
-        let plist: Value = plist::from_reader(cursor)
-            .map_err(|e| format!("Failed to parse PrivacyInfo.xcprivacy: {}", e))?;
-        if let Value::Dictionary(dict) = plist {
-            privacy_info.present = true;
-            privacy_info.tracking = dict.get("NSPrivacyTracking").and_then(|v| v.as_boolean());
-            privacy_info.tracking_domains = dict.get("NSPrivacyTrackingDomains").and_then(|v| v.as_array()).map(|arr| {
-                arr.iter().filter_map(|v| v.as_string().map(|s| s.to_string())).collect()
-            });
+    let cursor = std::io::Cursor::new(buffer);
+    let plist: Value = plist::from_reader(cursor)
+        .map_err(|e| format!("Failed to parse PrivacyInfo.xcprivacy: {}", e))?;
+    if let Value::Dictionary(dict) = plist {
+        privacy_manifest::validate_privacy_manifest(&dict, path, warnings);
+        privacy_info.present = true;
+        privacy_info.tracking = dict.get("NSPrivacyTracking").and_then(|v| v.as_boolean());
+        privacy_info.tracking_domains = dict.get("NSPrivacyTrackingDomains").and_then(|v| v.as_array()).map(|arr| {
+            arr.iter().filter_map(|v| v.as_string().map(|s| s.to_string())).collect()
+        });
 
-            if let Some(collected_data_types) = dict.get("NSPrivacyCollectedDataTypes").and_then(|v| v.as_array()) {
-                privacy_info.collected_data_types = Some(
-                    collected_data_types.iter().filter_map(|item| {
-                        if let Value::Dictionary(data_dict) = item {
-                            Some(CollectedDataType {
-                                data_type: data_dict.get("NSPrivacyCollectedDataType")?.as_string()?.to_string(),
-                                linked_to_user: data_dict.get("NSPrivacyCollectedDataTypeLinked")?.as_boolean()?,
-                                tracking: data_dict.get("NSPrivacyCollectedDataTypeTracking")?.as_boolean()?,
-                                purposes: data_dict.get("NSPrivacyCollectedDataTypePurposes")?.as_array()?.iter().filter_map(|v| v.as_string().map(|s| s.to_string())).collect(),
-                            })
-                        } else {
-                            None
-                        }
-                    }).collect::<Vec<CollectedDataType>>(),
-                );
-            }
+        if let Some(collected_data_types) = dict.get("NSPrivacyCollectedDataTypes").and_then(|v| v.as_array()) {
+            privacy_info.collected_data_types = Some(
+                collected_data_types.iter().filter_map(|item| {
+                    if let Value::Dictionary(data_dict) = item {
+                        Some(CollectedDataType {
+                            data_type: data_dict.get("NSPrivacyCollectedDataType")?.as_string()?.to_string(),
+                            linked_to_user: data_dict.get("NSPrivacyCollectedDataTypeLinked")?.as_boolean()?,
+                            tracking: data_dict.get("NSPrivacyCollectedDataTypeTracking")?.as_boolean()?,
+                            purposes: data_dict.get("NSPrivacyCollectedDataTypePurposes")?.as_array()?.iter().filter_map(|v| v.as_string().map(|s| s.to_string())).collect(),
+                        })
+                    } else {
+                        None
+                    }
+                }).collect::<Vec<CollectedDataType>>(),
+            );
+        }
 
-            if let Some(accessed_api_types) = dict.get("NSPrivacyAccessedAPITypes").and_then(|v| v.as_array()) {
-                privacy_info.accessed_api_types = Some(
-                    accessed_api_types.iter().filter_map(|item| {
-                        if let Value::Dictionary(api_dict) = item {
-                            Some(AccessedAPIType {
-                                api: api_dict.get("NSPrivacyAccessedAPIType")?.as_string()?.to_string(),
-                                reasons: api_dict.get("NSPrivacyAccessedAPITypeReasons")?.as_array()?.iter().filter_map(|v| v.as_string().map(|s| s.to_string())).collect(),
-                            })
-                        } else {
-                            None
-                        }
-                    }).collect::<Vec<AccessedAPIType>>(),
-                );
-            }
-        } else {
-            return Err("PrivacyInfo.xcprivacy is not a dictionary".to_string());
+        if let Some(accessed_api_types) = dict.get("NSPrivacyAccessedAPITypes").and_then(|v| v.as_array()) {
+            privacy_info.accessed_api_types = Some(
+                accessed_api_types.iter().filter_map(|item| {
+                    if let Value::Dictionary(api_dict) = item {
+                        Some(AccessedAPIType {
+                            api: api_dict.get("NSPrivacyAccessedAPIType")?.as_string()?.to_string(),
+                            reasons: api_dict.get("NSPrivacyAccessedAPITypeReasons")?.as_array()?.iter().filter_map(|v| v.as_string().map(|s| s.to_string())).collect(),
+                        })
+                    } else {
+                        None
+                    }
+                }).collect::<Vec<AccessedAPIType>>(),
+            );
         }
+    } else {
+        return Err("PrivacyInfo.xcprivacy is not a dictionary".to_string());
     }
 
     Ok(privacy_info)
@@ -93,37 +114,20 @@ pub fn extract_framework_name(plist: &Value) -> Option<String> {
     None
 }
 
-pub fn extract_framework_version(
-    archive: &mut ZipArchive<File>,
+/// Reads the Info.plist of a slice library (e.g. `Foo.framework/Info.plist`), if it has one.
+pub fn read_library_info_plist(
+    archive: &mut dyn ArchiveSource,
+    index: &ArchiveIndex,
     library_identifier: &str,
     library_path: &str
-) -> Option<String> {
-    let modules_path = format!("{}/{}", library_identifier, library_path);
-
-    let mut plist_buffer = None;
-    for i in 0..archive.len() {
-        let mut file = archive
-            .by_index(i)
-            .map_err(|e| format!("Failed to access file in archive: {}", e)).ok()?;
-        if file.name().contains(&modules_path) && file.name().ends_with("Info.plist") {
-            let mut buffer = Vec::new();
-            file.read_to_end(&mut buffer).map_err(|e| format!("Failed to read file content: {}", e)).ok()?;
-            plist_buffer = Some(buffer);
-            break;
-        }
-    }
-
-    if let Some(buffer) = plist_buffer {
-        let cursor = Cursor::new(buffer);
-        let plist: Value = plist::from_reader(cursor).map_err(|e| format!("Failed to read Info.plist: {}", e)).ok()?;
-
-        if let Value::Dictionary(dict) = plist {
-            if let Some(Value::String(version)) = dict.get("CFBundleShortVersionString") {
-                return Some(version.clone());
-            }
-        }
-    }
-    None
+) -> Option<Dictionary> {
+    // Nested bundles have their own Info.plist; the framework's is the shallowest one.
+    let file = index
+        .library_files(library_identifier, library_path)
+        .filter(|file| file.kind == FileKind::InfoPlist)
+        .min_by_key(|file| file.name.matches('/').count())?;
+    let buffer = archive.read_file(&file.name).ok()?;
+    plist::from_reader(Cursor::new(buffer)).ok()
 }
 
 pub fn check_mergeable_metadata(plist: &Value) -> bool {
@@ -141,7 +145,7 @@ pub fn check_mergeable_metadata(plist: &Value) -> bool {
     false
 }
 
-pub fn extract_built_for_distribution(archive: &mut ZipArchive<File>, plist: &Value) -> bool {
+pub fn extract_built_for_distribution(index: &ArchiveIndex, plist: &Value) -> bool {
     if let Value::Dictionary(dict) = plist {
         if let Some(Value::Boolean(built_for_distribution)) = dict.get("DTSDKBuild") {
             return *built_for_distribution;
@@ -149,19 +153,29 @@ pub fn extract_built_for_distribution(archive: &mut ZipArchive<File>, plist: &Va
     }
 
     // Check for .swiftinterface files in the Modules directory
-    for i in 0..archive.len() {
-        if let Ok(file) = archive.by_index(i) {
-            if file.name().ends_with(".swiftinterface") {
-                return true;
-            }
-        }
-    }
+    index.files_of_kind(FileKind::SwiftInterface).next().is_some()
+}
 
-    false
+/// Lists the `(LibraryIdentifier, BinaryPath)` of every slice declared in the xcframework's Info.plist.
+pub fn extract_slice_binaries(plist: &Value) -> Vec<(String, String)> {
+    plist
+        .as_dictionary()
+        .and_then(|dict| dict.get("AvailableLibraries"))
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|library| {
+            let lib_dict = library.as_dictionary()?;
+            let library_identifier = lib_dict.get("LibraryIdentifier")?.as_string()?.to_string();
+            let binary_path = lib_dict.get("BinaryPath")?.as_string()?.to_string();
+            Some((library_identifier, binary_path))
+        })
+        .collect()
 }
 
 pub fn extract_available_libraries(
-    archive: &mut ZipArchive<File>,
+    archive: &mut dyn ArchiveSource,
+    index: &ArchiveIndex,
     plist: &Value,
 ) -> Option<Vec<LibraryInfo>> {
     if let Value::Dictionary(dict) = plist {
@@ -172,19 +186,61 @@ pub fn extract_available_libraries(
                     let library_identifier = lib_dict.get("LibraryIdentifier")?.as_string()?.to_string();
                     let library_path = lib_dict.get("LibraryPath")?.as_string()?.to_string();
                     let mergeable_metadata = lib_dict.get("MergeableMetadata").and_then(|v| v.as_boolean());
-                    let supported_architectures = lib_dict.get("SupportedArchitectures")?.as_array()?.iter().filter_map(|v| v.as_string().map(|s| s.to_string())).collect();
+                    let supported_architectures: Vec<String> = lib_dict.get("SupportedArchitectures")?.as_array()?.iter().filter_map(|v| v.as_string().map(|s| s.to_string())).collect();
                     let supported_platform = lib_dict.get("SupportedPlatform")?.as_string()?.to_string();
                     let supported_platform_variant = lib_dict.get("SupportedPlatformVariant").and_then(|v| v.as_string()).map(|s| s.to_string());
                     let minimum_os_version = lib_dict.get("MinimumOSVersion").and_then(|v| v.as_string()).map(|s| s.to_string());
-                    let size = calculate_library_size(archive, &binary_path).ok();
-                    let marketing_version = extract_framework_version(archive, &library_identifier, &library_path);
-                    let dependencies = extract_dependencies(archive, &library_identifier, &library_path);
-                    let privacy_info = extract_privacy_info(archive, &library_identifier, &library_path).ok();
+                    let headers_path = lib_dict.get("HeadersPath").and_then(|v| v.as_string()).map(|s| s.to_string());
+                    let size_bytes = calculate_library_size(index, &library_identifier, &binary_path).ok();
+                    let size = size_bytes.map(format_size);
+                    let library_plist = read_library_info_plist(archive, index, &library_identifier, &library_path);
+                    let plist_string = |key: &str| library_plist.as_ref()?.get(key)?.as_string().map(str::to_string);
+                    let marketing_version = plist_string("CFBundleShortVersionString");
+                    let bundle_identifier = plist_string("CFBundleIdentifier");
+                    let dependencies = extract_dependencies(archive, index, &library_identifier, &library_path);
+                    let swift_interfaces = swift_details::extract_swift_interfaces(archive, index, &library_identifier, &library_path);
+
+                    let mut warnings = Vec::new();
+                    let privacy_manifests = extract_privacy_manifests(archive, index, &library_identifier, &mut warnings);
+                    let privacy_info = Some(privacy_manifest::merge_privacy_info(privacy_manifests.iter().map(|manifest| &manifest.privacy_info)));
+                    let binary_name = index.slice_path(&library_identifier, &binary_path);
+                    let binary = archive.read_file(&binary_name).ok();
+                    let images = match binary.as_deref().map(macho::parse_images) {
+                        Some(Ok(images)) => images,
+                        Some(Err(e)) => {
+                            warnings.push(Warning {
+                                code: "invalid-binary".to_string(),
+                                message: e,
+                                path: Some(binary_name.clone()),
+                            });
+                            Vec::new()
+                        }
+                        None => Vec::new(),
+                    };
+                    let expected_platform = macho::expected_platform(&supported_platform, supported_platform_variant.as_deref());
+                    let architectures = extract_architectures(&images, &binary_name, &supported_architectures, expected_platform, &mut warnings);
+                    check_build_platforms(&architectures, &binary_name, &supported_platform, supported_platform_variant.as_deref(), &mut warnings);
+                    check_install_names(&architectures, &binary_name, &mut warnings);
+                    let linked_libraries = extract_linked_libraries(&images);
+                    let required_reason_apis = required_reason::check_required_reason_apis(index, &library_identifier, &library_path, &binary_name, &images, privacy_info.as_ref(), &mut warnings);
+                    let clang_module = clang_module::extract_clang_module(archive, index, &library_identifier, &library_path, headers_path.as_deref(), &mut warnings);
+                    swift_details::check_distribution_flags(&swift_interfaces, &index.slice_path(&library_identifier, &library_path), &mut warnings);
+                    let linkage = extract_linkage(&images, &binary_name, &library_path, mergeable_metadata.unwrap_or(false), &mut warnings);
+                    let signature = code_signature::extract_library_signature(archive, index, &library_identifier, &library_path, &binary_name, &images, &mut warnings);
+
+                    // The deployment target recorded by the linker stands in for a missing MinimumOSVersion.
+                    let minimum_os_version = minimum_os_version.or_else(|| {
+                        architectures
+                            .iter()
+                            .filter_map(|architecture| architecture.minos.clone())
+                            .max_by(|a, b| compare_versions(a, b))
+                    });
 
                     Some(LibraryInfo {
                         binary_path,
                         library_identifier,
                         library_path,
+                        bundle_identifier,
                         marketing_version,
                         mergeable_metadata,
                         supported_architectures,
@@ -192,8 +248,18 @@ pub fn extract_available_libraries(
                         supported_platform_variant,
                         minimum_os_version,
                         size,
+                        size_bytes,
+                        linkage,
                         dependencies,
+                        linked_libraries,
                         privacy_info,
+                        privacy_manifests,
+                        required_reason_apis,
+                        swift_interfaces,
+                        clang_module,
+                        architectures,
+                        signature: Some(signature),
+                        warnings,
                     })
                 } else {
                     None
@@ -205,15 +271,191 @@ pub fn extract_available_libraries(
     None
 }
 
-fn extract_dependencies(archive: &mut ZipArchive<File>, library_identifier: &str, library_path: &str) -> Vec<String> {
+/// Reports the architectures actually present in the binary, warning when they
+/// differ from the `SupportedArchitectures` declared in the xcframework's Info.plist.
+fn extract_architectures(
+    images: &[ArchitectureImage],
+    binary_name: &str,
+    supported_architectures: &[String],
+    expected_platform: Option<u32>,
+    warnings: &mut Vec<Warning>,
+) -> Vec<ArchitectureInfo> {
+    let architectures: Vec<ArchitectureInfo> = images.iter().map(|image| macho::architecture_info(image, expected_platform)).collect();
+    if architectures.is_empty() {
+        return architectures;
+    }
+
+    for declared in supported_architectures {
+        if !architectures.iter().any(|architecture| &architecture.name == declared) {
+            warnings.push(Warning {
+                code: "architecture-mismatch".to_string(),
+                message: format!("Architecture '{}' is declared in Info.plist but missing from the binary", declared),
+                path: Some(binary_name.to_string()),
+            });
+        }
+    }
+    for architecture in &architectures {
+        if !supported_architectures.contains(&architecture.name) {
+            warnings.push(Warning {
+                code: "architecture-mismatch".to_string(),
+                message: format!("Architecture '{}' is present in the binary but not declared in Info.plist", architecture.name),
+                path: Some(binary_name.to_string()),
+            });
+        }
+    }
+
+    architectures
+}
+
+/// Warns about architectures whose Mach-O platform contradicts the slice's
+/// `SupportedPlatform`/`SupportedPlatformVariant` (e.g. a simulator slice built for devices).
+fn check_build_platforms(
+    architectures: &[ArchitectureInfo],
+    binary_name: &str,
+    supported_platform: &str,
+    supported_platform_variant: Option<&str>,
+    warnings: &mut Vec<Warning>,
+) {
+    let Some(expected) = macho::expected_platform(supported_platform, supported_platform_variant) else {
+        return;
+    };
+    let expected = macho::platform_name(expected);
+    let declared = match supported_platform_variant {
+        Some(variant) => format!("{} ({})", supported_platform, variant),
+        None => supported_platform.to_string(),
+    };
+
+    for architecture in architectures {
+        if let Some(platform) = &architecture.platform {
+            if platform != &expected {
+                warnings.push(Warning {
+                    code: "platform-mismatch".to_string(),
+                    message: format!(
+                        "Slice is declared for '{}' but its {} binary is built for '{}' (expected '{}')",
+                        declared, architecture.name, platform, expected
+                    ),
+                    path: Some(binary_name.to_string()),
+                });
+            }
+        }
+    }
+}
+
+/// Detects the linkage of a slice; every architecture is expected to agree.
+fn extract_linkage(
+    images: &[ArchitectureImage],
+    binary_name: &str,
+    library_path: &str,
+    mergeable_metadata: bool,
+    warnings: &mut Vec<Warning>,
+) -> Option<Linkage> {
+    let kinds: Vec<Linkage> = images
+        .iter()
+        .filter_map(|image| macho::linkage(image, library_path, mergeable_metadata))
+        .collect();
+    let first = *kinds.first()?;
+    if kinds.iter().any(|&kind| kind != first) {
+        warnings.push(Warning {
+            code: "mixed-linkage".to_string(),
+            message: "Architectures of the binary do not share the same linkage".to_string(),
+            path: Some(binary_name.to_string()),
+        });
+        return None;
+    }
+    Some(first)
+}
+
+/// Summarizes the linkage of all slices, with the embedding Xcode needs for it.
+pub fn summarize_linkage(libraries: &[LibraryInfo], warnings: &mut Vec<Warning>) -> (Option<Linkage>, Option<Embedding>) {
+    let mut kinds = libraries.iter().filter_map(|library| library.linkage);
+    let Some(first) = kinds.next() else {
+        return (None, None);
+    };
+    let embedding = first.embedding();
+    if kinds.clone().all(|kind| kind == first) {
+        (Some(first), Some(embedding))
+    } else if kinds.all(|kind| kind.embedding() == embedding) {
+        (None, Some(embedding))
+    } else {
+        warnings.push(Warning {
+            code: "mixed-linkage".to_string(),
+            message: "Slices mix static and dynamic linkage, so no single embedding setting fits every platform".to_string(),
+            path: None,
+        });
+        (None, None)
+    }
+}
+
+/// Warns about install names that are absolute paths outside of the system
+/// locations, which usually leak the machine the framework was built on.
+fn check_install_names(architectures: &[ArchitectureInfo], binary_name: &str, warnings: &mut Vec<Warning>) {
+    const SYSTEM_PREFIXES: [&str; 2] = ["/System/Library/", "/usr/lib/"];
+
+    for architecture in architectures {
+        if let Some(install_name) = &architecture.install_name {
+            if install_name.starts_with('/') && !SYSTEM_PREFIXES.iter().any(|prefix| install_name.starts_with(prefix)) {
+                warnings.push(Warning {
+                    code: "absolute-install-name".to_string(),
+                    message: format!(
+                        "Install name '{}' of the {} binary is an absolute path; frameworks are expected to use '@rpath/...'",
+                        install_name, architecture.name
+                    ),
+                    path: Some(binary_name.to_string()),
+                });
+            }
+        }
+    }
+}
+
+/// Collects the dynamic libraries linked by every architecture, merging identical
+/// load commands so each library is listed once with the architectures using it.
+fn extract_linked_libraries(images: &[ArchitectureImage]) -> Vec<LinkedLibrary> {
+    let mut linked_libraries: Vec<LinkedLibrary> = Vec::new();
+    for image in images {
+        let architecture = macho::architecture_name(image.cpu_type, image.cpu_subtype);
+        for file in &image.objects {
+            for dylib in macho::linked_dylibs(file) {
+                let current_version = macho::format_version(dylib.current_version);
+                let compatibility_version = macho::format_version(dylib.compatibility_version);
+                match linked_libraries.iter_mut().find(|library| {
+                    library.install_name == dylib.install_name
+                        && library.kind == dylib.kind
+                        && library.current_version == current_version
+                        && library.compatibility_version == compatibility_version
+                }) {
+                    Some(library) => {
+                        if !library.architectures.contains(&architecture) {
+                            library.architectures.push(architecture.clone());
+                        }
+                    }
+                    None => linked_libraries.push(LinkedLibrary {
+                        install_name: dylib.install_name,
+                        kind: dylib.kind,
+                        current_version,
+                        compatibility_version,
+                        architectures: vec![architecture.clone()],
+                    }),
+                }
+            }
+        }
+    }
+    linked_libraries
+}
+
+fn extract_dependencies(
+    archive: &mut dyn ArchiveSource,
+    index: &ArchiveIndex,
+    library_identifier: &str,
+    library_path: &str
+) -> Vec<String> {
     let mut dependencies = Vec::new();
-    let modules_path = format!("{}/{}/Modules", library_identifier, library_path);
+    let modules_path = format!("{}/Modules", library_path);
 
-    for i in 0..archive.len() {
-        let mut file = archive.by_index(i).unwrap();
-        if file.name().contains(&modules_path) && file.name().ends_with(".swiftinterface") {
-            let mut contents = String::new();
-            file.read_to_string(&mut contents).unwrap();
+    for file in index.library_files(library_identifier, &modules_path) {
+        if file.kind == FileKind::SwiftInterface {
+            let Ok(contents) = archive.read_to_string(&file.name) else {
+                continue;
+            };
             for line in contents.lines() {
                 if line.starts_with("import ") {
                     if let Some(dep) = line.split_whitespace().nth(1) {
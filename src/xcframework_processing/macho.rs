@@ -0,0 +1,397 @@
+use goblin::archive::Archive;
+use goblin::mach::constants::cputype::{
+    CPU_SUBTYPE_MASK, CPU_TYPE_ARM, CPU_TYPE_ARM64, CPU_TYPE_ARM64_32, CPU_TYPE_I386, CPU_TYPE_X86_64,
+};
+use goblin::mach::fat::FAT_MAGIC;
+use goblin::mach::header::{MH_DYLIB, MH_OBJECT};
+use goblin::mach::load_command::CommandVariant;
+use goblin::mach::{peek, MachO, MultiArch};
+use crate::framework_info::{ArchitectureInfo, BuildTool, LinkKind, Linkage};
+
+/// Magic at the start of a static `ar` archive.
+const AR_MAGIC: &[u8] = b"!<arch>\n";
+
+/// `LC_ATOM_INFO`, only emitted by the linker for mergeable libraries.
+const LC_ATOM_INFO: u32 = 0x36;
+
+/// Size of `build_version_command`, after which its `build_tool_version` entries follow.
+const SIZEOF_BUILD_VERSION_COMMAND: usize = 24;
+
+/// A parsed Mach-O file along with the bytes it was parsed from.
+pub struct MachOFile<'a> {
+    /// Member name when the file comes from a static archive, empty otherwise.
+    pub name: String,
+    pub macho: MachO<'a>,
+    pub bytes: &'a [u8],
+}
+
+/// One architecture of a slice binary, as described by its own Mach-O header(s).
+pub struct ArchitectureImage<'a> {
+    pub cpu_type: u32,
+    pub cpu_subtype: u32,
+    /// Whether the architecture is a static `ar` archive rather than a single Mach-O file.
+    pub is_archive: bool,
+    /// The Mach-O file itself, or every Mach-O object member of the archive.
+    pub objects: Vec<MachOFile<'a>>,
+}
+
+/// Splits a (possibly fat) binary into its architectures and parses the thin
+/// Mach-O headers of each one.
+pub fn parse_images(bytes: &[u8]) -> Result<Vec<ArchitectureImage<'_>>, String> {
+    if peek(bytes, 0).ok() == Some(FAT_MAGIC) {
+        let multi = MultiArch::new(bytes).map_err(|e| format!("Failed to parse fat header: {}", e))?;
+        let mut images = Vec::with_capacity(multi.narches);
+        for arch in multi.iter_arches() {
+            let arch = arch.map_err(|e| format!("Failed to parse fat architecture: {}", e))?;
+            let mut image = parse_thin(arch.slice(bytes))?;
+            // Archives without any object fall back to what the fat header says.
+            if image.objects.is_empty() {
+                image.cpu_type = arch.cputype;
+                image.cpu_subtype = arch.cpusubtype & !CPU_SUBTYPE_MASK;
+            }
+            images.push(image);
+        }
+        Ok(images)
+    } else {
+        Ok(vec![parse_thin(bytes)?])
+    }
+}
+
+fn parse_thin(bytes: &[u8]) -> Result<ArchitectureImage<'_>, String> {
+    if bytes.starts_with(AR_MAGIC) {
+        let archive = Archive::parse(bytes).map_err(|e| format!("Failed to parse static archive: {}", e))?;
+        let mut objects = Vec::new();
+        for i in 0..archive.len() {
+            let Some(member) = archive.get_at(i) else {
+                continue;
+            };
+            let start = member.offset as usize;
+            let Some(member_bytes) = bytes.get(start..start + member.size()) else {
+                continue;
+            };
+            // Symbol tables (`__.SYMDEF`) and other non Mach-O members are skipped.
+            if let Ok(macho) = MachO::parse(member_bytes, 0) {
+                objects.push(MachOFile { name: member.extended_name().to_string(), macho, bytes: member_bytes });
+            }
+        }
+        let (cpu_type, cpu_subtype) = objects
+            .first()
+            .map(|object| (object.macho.header.cputype, object.macho.header.cpusubtype & !CPU_SUBTYPE_MASK))
+            .unwrap_or_default();
+        Ok(ArchitectureImage { cpu_type, cpu_subtype, is_archive: true, objects })
+    } else {
+        let macho = MachO::parse(bytes, 0).map_err(|e| format!("Failed to parse Mach-O header: {}", e))?;
+        Ok(ArchitectureImage {
+            cpu_type: macho.header.cputype,
+            cpu_subtype: macho.header.cpusubtype & !CPU_SUBTYPE_MASK,
+            is_archive: false,
+            objects: vec![MachOFile { name: String::new(), macho, bytes }],
+        })
+    }
+}
+
+/// Returns the name Xcode uses for a CPU type/subtype pair (e.g. `arm64e`).
+pub fn architecture_name(cpu_type: u32, cpu_subtype: u32) -> String {
+    match (cpu_type, cpu_subtype) {
+        (CPU_TYPE_ARM64, 2) => "arm64e".to_string(),
+        (CPU_TYPE_ARM64, _) => "arm64".to_string(),
+        (CPU_TYPE_ARM64_32, _) => "arm64_32".to_string(),
+        (CPU_TYPE_X86_64, 8) => "x86_64h".to_string(),
+        (CPU_TYPE_X86_64, _) => "x86_64".to_string(),
+        (CPU_TYPE_I386, _) => "i386".to_string(),
+        (CPU_TYPE_ARM, 9) => "armv7".to_string(),
+        (CPU_TYPE_ARM, 11) => "armv7s".to_string(),
+        (CPU_TYPE_ARM, 12) => "armv7k".to_string(),
+        (CPU_TYPE_ARM, _) => "arm".to_string(),
+        _ => format!("unknown({:#x}/{:#x})", cpu_type, cpu_subtype),
+    }
+}
+
+/// Detects how an architecture of a slice binary links, from its Mach-O file type.
+///
+/// `library_path` tells frameworks apart from plain libraries, and `mergeable_metadata`
+/// is the `MergeableMetadata` flag of the xcframework's Info.plist.
+pub fn linkage(image: &ArchitectureImage, library_path: &str, mergeable_metadata: bool) -> Option<Linkage> {
+    let is_framework = library_path.ends_with(".framework");
+    if image.is_archive {
+        return Some(if is_framework { Linkage::StaticFramework } else { Linkage::StaticLibrary });
+    }
+
+    let file = image.objects.first()?;
+    match file.macho.header.filetype {
+        MH_OBJECT => Some(if is_framework { Linkage::StaticFramework } else { Linkage::StaticLibrary }),
+        MH_DYLIB => {
+            let has_atom_info = file.macho.load_commands.iter().any(|command| command.command.cmd() == LC_ATOM_INFO);
+            Some(if mergeable_metadata || has_atom_info {
+                Linkage::MergeableDynamicLibrary
+            } else if is_framework {
+                Linkage::DynamicFramework
+            } else {
+                Linkage::DynamicLibrary
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Dynamic library referenced by a load command of a Mach-O file.
+pub struct DylibReference {
+    pub install_name: String,
+    pub kind: LinkKind,
+    pub current_version: u32,
+    pub compatibility_version: u32,
+}
+
+/// Reads the `LC_LOAD_DYLIB`, `LC_LOAD_WEAK_DYLIB`, `LC_REEXPORT_DYLIB` and
+/// `LC_LOAD_UPWARD_DYLIB` commands of a Mach-O file, in load order.
+pub fn linked_dylibs(file: &MachOFile) -> Vec<DylibReference> {
+    file.macho
+        .load_commands
+        .iter()
+        .filter_map(|command| {
+            let (kind, dylib) = match command.command {
+                CommandVariant::LoadDylib(dylib) => (LinkKind::Load, dylib),
+                CommandVariant::LoadWeakDylib(dylib) => (LinkKind::Weak, dylib),
+                CommandVariant::ReexportDylib(dylib) => (LinkKind::Reexport, dylib),
+                CommandVariant::LoadUpwardDylib(dylib) => (LinkKind::Upward, dylib),
+                _ => return None,
+            };
+            // The name is an offset into the load command and must not run past its end.
+            let command_bytes = file.bytes.get(command.offset..command.offset.checked_add(command.command.cmdsize())?)?;
+            Some(DylibReference {
+                install_name: read_c_string(command_bytes, dylib.dylib.name as usize)?,
+                kind,
+                current_version: dylib.dylib.current_version,
+                compatibility_version: dylib.dylib.compatibility_version,
+            })
+        })
+        .collect()
+}
+
+/// Reads a NUL-terminated string, such as the `lc_str` of a load command.
+pub fn read_c_string(bytes: &[u8], offset: usize) -> Option<String> {
+    let tail = bytes.get(offset..)?;
+    let end = tail.iter().position(|&byte| byte == 0).unwrap_or(tail.len());
+    Some(String::from_utf8_lossy(&tail[..end]).into_owned())
+}
+
+/// Platform, deployment target and SDK recorded by the linker in a Mach-O file.
+pub struct BuildVersion {
+    pub platform: u32,
+    pub minos: u32,
+    pub sdk: u32,
+    /// `(tool, version)` pairs of `LC_BUILD_VERSION`.
+    pub tools: Vec<(u32, u32)>,
+}
+
+/// Mach-O platform identifiers (`PLATFORM_*` in `<mach-o/loader.h>`).
+pub const PLATFORM_MACOS: u32 = 1;
+pub const PLATFORM_IOS: u32 = 2;
+pub const PLATFORM_TVOS: u32 = 3;
+pub const PLATFORM_WATCHOS: u32 = 4;
+pub const PLATFORM_BRIDGEOS: u32 = 5;
+pub const PLATFORM_MACCATALYST: u32 = 6;
+pub const PLATFORM_IOSSIMULATOR: u32 = 7;
+pub const PLATFORM_TVOSSIMULATOR: u32 = 8;
+pub const PLATFORM_WATCHOSSIMULATOR: u32 = 9;
+pub const PLATFORM_DRIVERKIT: u32 = 10;
+pub const PLATFORM_VISIONOS: u32 = 11;
+pub const PLATFORM_VISIONOSSIMULATOR: u32 = 12;
+
+/// Reads the `LC_BUILD_VERSION` commands, or the older `LC_VERSION_MIN_*` command, of a
+/// Mach-O file. Zippered binaries carry one `LC_BUILD_VERSION` for macOS and one for Mac Catalyst.
+pub fn build_versions(file: &MachOFile) -> Vec<BuildVersion> {
+    let is_intel = matches!(file.macho.header.cputype, CPU_TYPE_X86_64 | CPU_TYPE_I386);
+    let mut builds = Vec::new();
+    let mut version_min = None;
+    for command in &file.macho.load_commands {
+        let (platform, minos, sdk) = match command.command {
+            CommandVariant::BuildVersion(build) => {
+                let tools = (0..build.ntools as usize)
+                    .filter_map(|i| {
+                        let start = command.offset + SIZEOF_BUILD_VERSION_COMMAND + i * 8;
+                        Some((read_u32(file.bytes, start)?, read_u32(file.bytes, start + 4)?))
+                    })
+                    .collect();
+                builds.push(BuildVersion { platform: build.platform, minos: build.minos, sdk: build.sdk, tools });
+                continue;
+            }
+            CommandVariant::VersionMinMacosx(version) => (PLATFORM_MACOS, version.version, version.sdk),
+            // Before LC_BUILD_VERSION, simulator binaries were told apart only by their Intel CPU.
+            CommandVariant::VersionMinIphoneos(version) if is_intel => (PLATFORM_IOSSIMULATOR, version.version, version.sdk),
+            CommandVariant::VersionMinIphoneos(version) => (PLATFORM_IOS, version.version, version.sdk),
+            CommandVariant::VersionMinTvos(version) if is_intel => (PLATFORM_TVOSSIMULATOR, version.version, version.sdk),
+            CommandVariant::VersionMinTvos(version) => (PLATFORM_TVOS, version.version, version.sdk),
+            CommandVariant::VersionMinWatchos(version) if is_intel => (PLATFORM_WATCHOSSIMULATOR, version.version, version.sdk),
+            CommandVariant::VersionMinWatchos(version) => (PLATFORM_WATCHOS, version.version, version.sdk),
+            _ => continue,
+        };
+        if version_min.is_none() {
+            version_min = Some(BuildVersion { platform, minos, sdk, tools: Vec::new() });
+        }
+    }
+    if builds.is_empty() {
+        builds.extend(version_min);
+    }
+    builds
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let word = bytes.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+}
+
+/// Returns the name of a Mach-O platform, as printed by `vtool`/`otool`.
+pub fn platform_name(platform: u32) -> String {
+    match platform {
+        PLATFORM_MACOS => "macos".to_string(),
+        PLATFORM_IOS => "ios".to_string(),
+        PLATFORM_TVOS => "tvos".to_string(),
+        PLATFORM_WATCHOS => "watchos".to_string(),
+        PLATFORM_BRIDGEOS => "bridgeos".to_string(),
+        PLATFORM_MACCATALYST => "maccatalyst".to_string(),
+        PLATFORM_IOSSIMULATOR => "iossimulator".to_string(),
+        PLATFORM_TVOSSIMULATOR => "tvossimulator".to_string(),
+        PLATFORM_WATCHOSSIMULATOR => "watchossimulator".to_string(),
+        PLATFORM_DRIVERKIT => "driverkit".to_string(),
+        PLATFORM_VISIONOS => "visionos".to_string(),
+        PLATFORM_VISIONOSSIMULATOR => "visionossimulator".to_string(),
+        _ => format!("unknown({})", platform),
+    }
+}
+
+/// Returns the Mach-O platform a slice must be built for, given the
+/// `SupportedPlatform` and `SupportedPlatformVariant` of the xcframework.
+pub fn expected_platform(supported_platform: &str, supported_platform_variant: Option<&str>) -> Option<u32> {
+    match (supported_platform, supported_platform_variant) {
+        ("macos", None) => Some(PLATFORM_MACOS),
+        ("ios", None) => Some(PLATFORM_IOS),
+        ("ios", Some("simulator")) => Some(PLATFORM_IOSSIMULATOR),
+        ("ios", Some("maccatalyst")) => Some(PLATFORM_MACCATALYST),
+        ("tvos", None) => Some(PLATFORM_TVOS),
+        ("tvos", Some("simulator")) => Some(PLATFORM_TVOSSIMULATOR),
+        ("watchos", None) => Some(PLATFORM_WATCHOS),
+        ("watchos", Some("simulator")) => Some(PLATFORM_WATCHOSSIMULATOR),
+        ("xros", None) | ("visionos", None) => Some(PLATFORM_VISIONOS),
+        ("xros", Some("simulator")) | ("visionos", Some("simulator")) => Some(PLATFORM_VISIONOSSIMULATOR),
+        ("driverkit", None) => Some(PLATFORM_DRIVERKIT),
+        _ => None,
+    }
+}
+
+/// Returns the name of a build tool of `LC_BUILD_VERSION`.
+fn tool_name(tool: u32) -> String {
+    match tool {
+        1 => "clang".to_string(),
+        2 => "swift".to_string(),
+        3 => "ld".to_string(),
+        4 => "lld".to_string(),
+        _ => format!("unknown({})", tool),
+    }
+}
+
+/// Formats a version encoded in nibbles `xxxx.yy.zz`, omitting a zero patch.
+pub fn format_version(version: u32) -> String {
+    let (major, minor, patch) = (version >> 16, (version >> 8) & 0xff, version & 0xff);
+    if patch == 0 {
+        format!("{}.{}", major, minor)
+    } else {
+        format!("{}.{}.{}", major, minor, patch)
+    }
+}
+
+/// Reads the `LC_UUID` of a Mach-O file, formatted as `dwarfdump --uuid` prints it.
+pub fn uuid(file: &MachOFile) -> Option<String> {
+    file.macho.load_commands.iter().find_map(|command| match command.command {
+        CommandVariant::Uuid(uuid) => Some(format_uuid(&uuid.uuid)),
+        _ => None,
+    })
+}
+
+fn format_uuid(bytes: &[u8; 16]) -> String {
+    let hex: String = bytes.iter().map(|byte| format!("{:02X}", byte)).collect();
+    format!("{}-{}-{}-{}-{}", &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32])
+}
+
+/// Builds the per-architecture report of an image. The build version is the one of
+/// `platform`, the platform the slice is expected to be built for, when the binary has several.
+pub fn architecture_info(image: &ArchitectureImage, platform: Option<u32>) -> ArchitectureInfo {
+    // Every object of a static archive is built for the same platform; the first one is enough.
+    let mut builds = image.objects.iter().map(build_versions).find(|builds| !builds.is_empty()).unwrap_or_default();
+    let position = builds.iter().position(|build| Some(build.platform) == platform).unwrap_or(0);
+    let build = (position < builds.len()).then(|| builds.swap_remove(position));
+    // Install name, rpaths and UUID only exist in linked images, not in archived objects.
+    let linked = image.objects.first().filter(|_| !image.is_archive);
+
+    ArchitectureInfo {
+        name: architecture_name(image.cpu_type, image.cpu_subtype),
+        cpu_type: image.cpu_type,
+        cpu_subtype: image.cpu_subtype,
+        platform: build.as_ref().map(|build| platform_name(build.platform)),
+        minos: build.as_ref().map(|build| format_version(build.minos)),
+        sdk: build.as_ref().map(|build| format_version(build.sdk)),
+        tools: build
+            .map(|build| build.tools.iter().map(|&(tool, version)| BuildTool {
+                name: tool_name(tool),
+                version: format_version(version),
+            }).collect())
+            .unwrap_or_default(),
+        install_name: linked.and_then(|file| file.macho.name.map(|name| name.to_string())),
+        rpaths: linked
+            .map(|file| file.macho.rpaths.iter().map(|rpath| rpath.to_string()).collect())
+            .unwrap_or_default(),
+        uuid: linked.and_then(uuid),
+        dsym: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LC_VERSION_MIN_IPHONEOS: u32 = 0x25;
+    const LC_BUILD_VERSION: u32 = 0x32;
+
+    /// Thin arm64 dylib made of the given load commands, as `(cmd, fields after cmdsize)`.
+    fn dylib(commands: &[(u32, Vec<u32>)]) -> Vec<u8> {
+        let sizeofcmds: usize = commands.iter().map(|(_, fields)| 8 + fields.len() * 4).sum();
+        let header = [0xfeedfacf, CPU_TYPE_ARM64, 0, MH_DYLIB, commands.len() as u32, sizeofcmds as u32, 0, 0];
+        let mut words = header.to_vec();
+        for (cmd, fields) in commands {
+            words.extend([*cmd, 8 + fields.len() as u32 * 4]);
+            words.extend(fields);
+        }
+        words.iter().flat_map(|word| word.to_le_bytes()).collect()
+    }
+
+    fn platform(bytes: &[u8], expected: Option<u32>) -> (Option<String>, Option<String>) {
+        let images = parse_images(bytes).expect("valid Mach-O");
+        let info = architecture_info(&images[0], expected);
+        (info.platform, info.minos)
+    }
+
+    #[test]
+    fn reports_the_build_version_of_the_slice_for_zippered_binaries() {
+        let bytes = dylib(&[
+            (LC_BUILD_VERSION, vec![PLATFORM_MACOS, 0x000b_0000, 0x000e_0000, 0]),
+            (LC_BUILD_VERSION, vec![PLATFORM_MACCATALYST, 0x000e_0000, 0x0011_0000, 0]),
+        ]);
+        let images = parse_images(&bytes).unwrap();
+        let platforms: Vec<u32> = build_versions(&images[0].objects[0]).iter().map(|build| build.platform).collect();
+        assert_eq!(platforms, [PLATFORM_MACOS, PLATFORM_MACCATALYST]);
+
+        let expect = |platform: &str, minos: &str| (Some(platform.to_string()), Some(minos.to_string()));
+        assert_eq!(platform(&bytes, Some(PLATFORM_MACOS)), expect("macos", "11.0"));
+        assert_eq!(platform(&bytes, Some(PLATFORM_MACCATALYST)), expect("maccatalyst", "14.0"));
+        // Other slices see the first one, which is reported as a mismatch.
+        assert_eq!(platform(&bytes, Some(PLATFORM_IOS)), expect("macos", "11.0"));
+        assert_eq!(platform(&bytes, None), expect("macos", "11.0"));
+    }
+
+    #[test]
+    fn falls_back_to_the_minimum_version_command() {
+        let bytes = dylib(&[(LC_VERSION_MIN_IPHONEOS, vec![0x000c_0000, 0x0011_0000])]);
+        assert_eq!(platform(&bytes, Some(PLATFORM_IOS)), (Some("ios".to_string()), Some("12.0".to_string())));
+        assert_eq!(platform(&dylib(&[]), Some(PLATFORM_IOS)), (None, None));
+    }
+}
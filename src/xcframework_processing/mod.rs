@@ -1,16 +1,41 @@
-use std::fs::File;
-use std::io::{Cursor, Read};
-use std::path::Path;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
 use plist::Value;
-use zip::ZipArchive;
-use crate::framework_info::FrameworkInfo;
+use crate::framework_info::{FrameworkInfo, SymbolTable};
+use crate::xcframework_processing::archive_index::ArchiveIndex;
+use crate::xcframework_processing::symbols::SymbolFilter;
 
+pub mod archive_index;
+pub mod archive_source;
+pub mod clang_module;
+pub mod code_signature;
+pub mod dsym;
 pub mod extractors;
+pub mod macho;
+pub mod privacy_manifest;
+pub mod required_reason;
+pub mod sdk_requirements;
+pub mod signature_verification;
+pub mod swift_api;
 pub mod swift_details;
+pub mod symbols;
 
-pub fn collect_framework_info<P: AsRef<Path>>(zip_path: P) -> Result<FrameworkInfo, String> {
-    let file = File::open(&zip_path).map_err(|e| format!("Failed to open the zip file: {}", e))?;
-    let mut archive = ZipArchive::new(file).map_err(|e| format!("Failed to read the zip file: {}", e))?;
+/// Optional inputs of [`collect_framework_info`].
+#[derive(Debug, Clone, Default)]
+pub struct CollectOptions {
+    /// Directory with dSYM bundles shipped separately from the framework.
+    pub dsym_dir: Option<PathBuf>,
+    /// Whether to build the public API inventory of the Swift module.
+    pub api: bool,
+    /// File replacing the built-in list of commonly used third-party SDKs.
+    pub sdk_list: Option<PathBuf>,
+}
+
+/// Collects the information of a framework stored either as a zip file or as an
+/// expanded `.xcframework` directory.
+pub fn collect_framework_info<P: AsRef<Path>>(framework_path: P, options: &CollectOptions) -> Result<FrameworkInfo, String> {
+    let mut archive = archive_source::open_archive(framework_path)?;
+    let index = ArchiveIndex::build(archive.entries()?);
 
     let mut framework_info = FrameworkInfo {
         framework_name: None,
@@ -20,32 +45,74 @@ pub fn collect_framework_info<P: AsRef<Path>>(zip_path: P) -> Result<FrameworkIn
         swift_version: None,
         library_evolution_enabled: false,
         built_for_distribution: false,
+        linkage: None,
+        embed: None,
         available_libraries: None,
+        signature: None,
+        sdk_requirements: None,
+        api: None,
+        warnings: Vec::new(),
     };
 
-    let mut plist_buffer = None;
-    for i in 0..archive.len() {
-        let mut file = archive.by_index(i).map_err(|e| format!("Failed to access file in archive: {}", e))?;
-        if file.name().ends_with("Info.plist") {
-            let mut buffer = Vec::new();
-            file.read_to_end(&mut buffer).map_err(|e| format!("Failed to read file content: {}", e))?;
-            plist_buffer = Some(buffer);
-            break;
-        }
-    }
-
-    if let Some(buffer) = plist_buffer {
+    if let Some(file) = index.root_info_plist() {
+        let buffer = archive.read_file(&file.name)?;
         let cursor = Cursor::new(buffer);
         let plist: Value = plist::from_reader(cursor).map_err(|e| format!("Failed to read Info.plist: {}", e))?;
 
         framework_info.framework_name = extractors::extract_framework_name(&plist);
         framework_info.is_mergeable = extractors::check_mergeable_metadata(&plist);
-        swift_details::extract_swift_details(&mut archive, &mut framework_info);
-        framework_info.built_for_distribution = extractors::extract_built_for_distribution(&mut archive, &plist);
-        framework_info.available_libraries = extractors::extract_available_libraries(&mut archive, &plist);
+        framework_info.built_for_distribution = extractors::extract_built_for_distribution(&index, &plist);
+        framework_info.signature = Some(code_signature::extract_framework_signature(archive.as_mut(), &index, &mut framework_info.warnings));
+        framework_info.available_libraries = extractors::extract_available_libraries(archive.as_mut(), &index, &plist);
+        swift_details::summarize_swift_details(&mut framework_info);
+        if let Some(libraries) = framework_info.available_libraries.as_deref().filter(|_| options.api) {
+            framework_info.api = Some(swift_api::extract_api(archive.as_mut(), &index, libraries));
+        }
+        if let Some(libraries) = &mut framework_info.available_libraries {
+            let library_identifiers: Vec<String> = libraries.iter().map(|library| library.library_identifier.clone()).collect();
+            let dsyms = dsym::collect_dsyms(archive.as_mut(), &index, &library_identifiers, options.dsym_dir.as_deref())?;
+            dsym::match_dsyms(libraries, &index, &dsyms);
+            (framework_info.linkage, framework_info.embed) = extractors::summarize_linkage(libraries, &mut framework_info.warnings);
+        }
+        let sdks = match &options.sdk_list {
+            Some(path) => sdk_requirements::load_sdk_list(path)?,
+            None => sdk_requirements::parse_sdk_list(sdk_requirements::COMMONLY_USED_SDKS),
+        };
+        let mut warnings = Vec::new();
+        framework_info.sdk_requirements = Some(sdk_requirements::check_sdk_requirements(&framework_info, &sdks, &mut warnings));
+        framework_info.warnings.extend(warnings);
     } else {
         return Err("Info.plist not found in the archive".to_string());
     }
 
     Ok(framework_info)
 }
+
+/// Collects the symbols of every architecture of every slice of a framework, keeping
+/// the slices, architectures and symbols selected by `filter`.
+pub fn collect_symbols<P: AsRef<Path>>(framework_path: P, filter: &SymbolFilter) -> Result<Vec<SymbolTable>, String> {
+    let mut archive = archive_source::open_archive(framework_path)?;
+    let index = ArchiveIndex::build(archive.entries()?);
+    let file = index.root_info_plist().ok_or_else(|| "Info.plist not found in the archive".to_string())?;
+    let buffer = archive.read_file(&file.name)?;
+    let plist: Value = plist::from_reader(Cursor::new(buffer)).map_err(|e| format!("Failed to read Info.plist: {}", e))?;
+
+    let mut tables = Vec::new();
+    for (library_identifier, binary_path) in extractors::extract_slice_binaries(&plist) {
+        if filter.library_identifier.as_ref().is_some_and(|identifier| *identifier != library_identifier) {
+            continue;
+        }
+        let binary = archive.read_file(&index.slice_path(&library_identifier, &binary_path))?;
+        for image in macho::parse_images(&binary)? {
+            let architecture = macho::architecture_name(image.cpu_type, image.cpu_subtype);
+            if filter.architecture.as_ref().is_some_and(|name| *name != architecture) {
+                continue;
+            }
+            let mut symbols = symbols::image_symbols(&image);
+            symbols::demangle_symbols(&mut symbols);
+            symbols.retain(|symbol| filter.matches(symbol));
+            tables.push(SymbolTable { library_identifier: library_identifier.clone(), architecture, symbols });
+        }
+    }
+    Ok(tables)
+}
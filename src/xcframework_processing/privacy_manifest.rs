@@ -0,0 +1,508 @@
+use std::collections::HashSet;
+use plist::{Dictionary, Value};
+use crate::framework_info::{AccessedAPIType, CollectedDataType, PrivacyInfo, Warning};
+
+/// Values of `NSPrivacyCollectedDataType`, with their App Store Connect category and name.
+pub(crate) const DATA_TYPES: &[(&str, &str, &str)] = &[
+    ("NSPrivacyCollectedDataTypeName", "Contact Info", "Name"),
+    ("NSPrivacyCollectedDataTypeEmailAddress", "Contact Info", "Email Address"),
+    ("NSPrivacyCollectedDataTypePhoneNumber", "Contact Info", "Phone Number"),
+    ("NSPrivacyCollectedDataTypePhysicalAddress", "Contact Info", "Physical Address"),
+    ("NSPrivacyCollectedDataTypeOtherUserContactInfo", "Contact Info", "Other User Contact Info"),
+    ("NSPrivacyCollectedDataTypeHealth", "Health & Fitness", "Health"),
+    ("NSPrivacyCollectedDataTypeFitness", "Health & Fitness", "Fitness"),
+    ("NSPrivacyCollectedDataTypePaymentInfo", "Financial Info", "Payment Info"),
+    ("NSPrivacyCollectedDataTypeCreditInfo", "Financial Info", "Credit Info"),
+    ("NSPrivacyCollectedDataTypeOtherFinancialInfo", "Financial Info", "Other Financial Info"),
+    ("NSPrivacyCollectedDataTypePreciseLocation", "Location", "Precise Location"),
+    ("NSPrivacyCollectedDataTypeCoarseLocation", "Location", "Coarse Location"),
+    ("NSPrivacyCollectedDataTypeSensitiveInfo", "Sensitive Info", "Sensitive Info"),
+    ("NSPrivacyCollectedDataTypeContacts", "Contacts", "Contacts"),
+    ("NSPrivacyCollectedDataTypeEmailsOrTextMessages", "User Content", "Emails or Text Messages"),
+    ("NSPrivacyCollectedDataTypePhotosorVideos", "User Content", "Photos or Videos"),
+    ("NSPrivacyCollectedDataTypeAudioData", "User Content", "Audio Data"),
+    ("NSPrivacyCollectedDataTypeGameplayContent", "User Content", "Gameplay Content"),
+    ("NSPrivacyCollectedDataTypeCustomerSupport", "User Content", "Customer Support"),
+    ("NSPrivacyCollectedDataTypeOtherUserContent", "User Content", "Other User Content"),
+    ("NSPrivacyCollectedDataTypeBrowsingHistory", "Browsing History", "Browsing History"),
+    ("NSPrivacyCollectedDataTypeSearchHistory", "Search History", "Search History"),
+    ("NSPrivacyCollectedDataTypeUserID", "Identifiers", "User ID"),
+    ("NSPrivacyCollectedDataTypeDeviceID", "Identifiers", "Device ID"),
+    ("NSPrivacyCollectedDataTypePurchaseHistory", "Purchases", "Purchase History"),
+    ("NSPrivacyCollectedDataTypeProductInteraction", "Usage Data", "Product Interaction"),
+    ("NSPrivacyCollectedDataTypeAdvertisingData", "Usage Data", "Advertising Data"),
+    ("NSPrivacyCollectedDataTypeOtherUsageData", "Usage Data", "Other Usage Data"),
+    ("NSPrivacyCollectedDataTypeCrashData", "Diagnostics", "Crash Data"),
+    ("NSPrivacyCollectedDataTypePerformanceData", "Diagnostics", "Performance Data"),
+    ("NSPrivacyCollectedDataTypeOtherDiagnosticData", "Diagnostics", "Other Diagnostic Data"),
+    ("NSPrivacyCollectedDataTypeEnvironmentScanning", "Surroundings", "Environment Scanning"),
+    ("NSPrivacyCollectedDataTypeHands", "Body", "Hands"),
+    ("NSPrivacyCollectedDataTypeHead", "Body", "Head"),
+    ("NSPrivacyCollectedDataTypeOtherDataTypes", "Other Data", "Other Data Types"),
+];
+
+/// Values of `NSPrivacyCollectedDataTypePurposes`, with their App Store Connect name.
+pub(crate) const PURPOSES: &[(&str, &str)] = &[
+    ("NSPrivacyCollectedDataTypePurposeThirdPartyAdvertising", "Third-Party Advertising"),
+    ("NSPrivacyCollectedDataTypePurposeDeveloperAdvertising", "Developer's Advertising or Marketing"),
+    ("NSPrivacyCollectedDataTypePurposeAnalytics", "Analytics"),
+    ("NSPrivacyCollectedDataTypePurposeProductPersonalization", "Product Personalization"),
+    ("NSPrivacyCollectedDataTypePurposeAppFunctionality", "App Functionality"),
+    ("NSPrivacyCollectedDataTypePurposeOther", "Other Purposes"),
+];
+
+/// Values of `NSPrivacyAccessedAPIType` with the reason codes approved for each of them.
+const API_CATEGORIES: &[(&str, &[&str])] = &[
+    ("NSPrivacyAccessedAPICategoryFileTimestamp", &["DDA9.1", "C617.1", "3B52.1", "0A2A.1"]),
+    ("NSPrivacyAccessedAPICategorySystemBootTime", &["35F9.1", "8FFB.1", "3D61.1"]),
+    ("NSPrivacyAccessedAPICategoryDiskSpace", &["85F4.1", "E174.1", "7D9E.1", "B728.1"]),
+    ("NSPrivacyAccessedAPICategoryActiveKeyboards", &["3EC4.1", "54BD.1"]),
+    ("NSPrivacyAccessedAPICategoryUserDefaults", &["CA92.1", "1C8F.1", "C56D.1", "AC6B.1"]),
+];
+
+const MANIFEST_KEYS: &[&str] = &[
+    "NSPrivacyTracking",
+    "NSPrivacyTrackingDomains",
+    "NSPrivacyCollectedDataTypes",
+    "NSPrivacyAccessedAPITypes",
+];
+const DATA_TYPE_KEYS: &[&str] = &[
+    "NSPrivacyCollectedDataType",
+    "NSPrivacyCollectedDataTypeLinked",
+    "NSPrivacyCollectedDataTypeTracking",
+    "NSPrivacyCollectedDataTypePurposes",
+];
+const API_TYPE_KEYS: &[&str] = &["NSPrivacyAccessedAPIType", "NSPrivacyAccessedAPITypeReasons"];
+
+/// Checks a `PrivacyInfo.xcprivacy` against the keys and values Apple accepts, including the
+/// entries left out of the parsed [`crate::framework_info::PrivacyInfo`].
+pub fn validate_privacy_manifest(manifest: &Dictionary, path: &str, warnings: &mut Vec<Warning>) {
+    let mut validator = Validator { path, warnings };
+    validator.unknown_keys(manifest, MANIFEST_KEYS, "privacy manifest");
+
+    let tracking = match manifest.get("NSPrivacyTracking") {
+        Some(Value::Boolean(tracking)) => *tracking,
+        Some(_) => {
+            validator.warn("invalid-privacy-value", "NSPrivacyTracking is not a boolean".to_string());
+            false
+        }
+        None => false,
+    };
+    let domains = manifest
+        .get("NSPrivacyTrackingDomains")
+        .and_then(|domains| validator.strings(domains, "NSPrivacyTrackingDomains"))
+        .unwrap_or_default();
+    for domain in duplicates(&domains) {
+        validator.warn("duplicate-privacy-entry", format!("Tracking domain \"{}\" is listed more than once", domain));
+    }
+    if domains.iter().any(|domain| domain.trim().is_empty()) {
+        validator.warn("invalid-privacy-value", "NSPrivacyTrackingDomains contains an empty domain".to_string());
+    }
+    if tracking && domains.is_empty() {
+        validator.warn(
+            "tracking-without-domains",
+            "NSPrivacyTracking is true but NSPrivacyTrackingDomains lists no domain".to_string(),
+        );
+    }
+
+    if let Some(data_types) = manifest.get("NSPrivacyCollectedDataTypes") {
+        validator.collected_data_types(data_types);
+    }
+    if let Some(api_types) = manifest.get("NSPrivacyAccessedAPITypes") {
+        validator.accessed_api_types(api_types);
+    }
+}
+
+/// Merges privacy manifests into the one an app embedding them ends up declaring: tracking
+/// if any manifest tracks, and the union of domains, data types and API reasons. Data types
+/// collected by several manifests are linked or tracking if any of them says so.
+pub fn merge_privacy_info<'a>(manifests: impl IntoIterator<Item = &'a PrivacyInfo>) -> PrivacyInfo {
+    let mut merged = PrivacyInfo {
+        present: false,
+        tracking: None,
+        tracking_domains: None,
+        collected_data_types: None,
+        accessed_api_types: None,
+    };
+    for info in manifests {
+        merged.present |= info.present;
+        if let Some(tracking) = info.tracking {
+            merged.tracking = Some(merged.tracking.unwrap_or(false) || tracking);
+        }
+        if let Some(domains) = &info.tracking_domains {
+            let merged_domains = merged.tracking_domains.get_or_insert_with(Vec::new);
+            for domain in domains {
+                if !merged_domains.contains(domain) {
+                    merged_domains.push(domain.clone());
+                }
+            }
+        }
+        if let Some(data_types) = &info.collected_data_types {
+            let merged_data_types = merged.collected_data_types.get_or_insert_with(Vec::new);
+            for data_type in data_types {
+                match merged_data_types.iter_mut().find(|merged| merged.data_type == data_type.data_type) {
+                    Some(merged) => {
+                        merged.linked_to_user |= data_type.linked_to_user;
+                        merged.tracking |= data_type.tracking;
+                        merge_strings(&mut merged.purposes, &data_type.purposes);
+                    }
+                    None => {
+                        let mut purposes = Vec::new();
+                        merge_strings(&mut purposes, &data_type.purposes);
+                        merged_data_types.push(CollectedDataType { purposes, ..data_type.clone() });
+                    }
+                }
+            }
+        }
+        if let Some(api_types) = &info.accessed_api_types {
+            let merged_api_types = merged.accessed_api_types.get_or_insert_with(Vec::new);
+            for api_type in api_types {
+                match merged_api_types.iter_mut().find(|merged| merged.api == api_type.api) {
+                    Some(merged) => merge_strings(&mut merged.reasons, &api_type.reasons),
+                    None => {
+                        let mut reasons = Vec::new();
+                        merge_strings(&mut reasons, &api_type.reasons);
+                        merged_api_types.push(AccessedAPIType { api: api_type.api.clone(), reasons });
+                    }
+                }
+            }
+        }
+    }
+    merged
+}
+
+/// Builds the `PrivacyInfo.xcprivacy` property list declaring a manifest.
+pub fn privacy_manifest_plist(info: &PrivacyInfo) -> Value {
+    let strings = |values: &[String]| Value::Array(values.iter().cloned().map(Value::String).collect());
+    let mut manifest = Dictionary::new();
+    manifest.insert("NSPrivacyTracking".to_string(), Value::Boolean(info.tracking.unwrap_or(false)));
+    manifest.insert(
+        "NSPrivacyTrackingDomains".to_string(),
+        strings(info.tracking_domains.as_deref().unwrap_or_default()),
+    );
+    let data_types = info.collected_data_types.iter().flatten().map(|data_type| {
+        let mut entry = Dictionary::new();
+        entry.insert("NSPrivacyCollectedDataType".to_string(), Value::String(data_type.data_type.clone()));
+        entry.insert("NSPrivacyCollectedDataTypeLinked".to_string(), Value::Boolean(data_type.linked_to_user));
+        entry.insert("NSPrivacyCollectedDataTypeTracking".to_string(), Value::Boolean(data_type.tracking));
+        entry.insert("NSPrivacyCollectedDataTypePurposes".to_string(), strings(&data_type.purposes));
+        Value::Dictionary(entry)
+    });
+    manifest.insert("NSPrivacyCollectedDataTypes".to_string(), Value::Array(data_types.collect()));
+    let api_types = info.accessed_api_types.iter().flatten().map(|api_type| {
+        let mut entry = Dictionary::new();
+        entry.insert("NSPrivacyAccessedAPIType".to_string(), Value::String(api_type.api.clone()));
+        entry.insert("NSPrivacyAccessedAPITypeReasons".to_string(), strings(&api_type.reasons));
+        Value::Dictionary(entry)
+    });
+    manifest.insert("NSPrivacyAccessedAPITypes".to_string(), Value::Array(api_types.collect()));
+    Value::Dictionary(manifest)
+}
+
+/// Appends the values `into` does not hold yet, dropping empty ones.
+fn merge_strings(into: &mut Vec<String>, values: &[String]) {
+    for value in values {
+        if !value.is_empty() && !into.contains(value) {
+            into.push(value.clone());
+        }
+    }
+}
+
+struct Validator<'a> {
+    path: &'a str,
+    warnings: &'a mut Vec<Warning>,
+}
+
+impl Validator<'_> {
+    fn warn(&mut self, code: &str, message: String) {
+        self.warnings.push(Warning {
+            code: code.to_string(),
+            message,
+            path: Some(self.path.to_string()),
+        });
+    }
+
+    fn unknown_keys(&mut self, dict: &Dictionary, known: &[&str], location: &str) {
+        for key in dict.keys().filter(|key| !known.contains(&key.as_str())) {
+            self.warn("unknown-privacy-key", format!("Unknown key {} in {}", key, location));
+        }
+    }
+
+    /// Strings of an array, warning about the array or elements of another type.
+    fn strings<'v>(&mut self, value: &'v Value, location: &str) -> Option<Vec<&'v str>> {
+        let Some(array) = value.as_array() else {
+            self.warn("invalid-privacy-value", format!("{} is not an array", location));
+            return None;
+        };
+        let strings: Vec<&str> = array.iter().filter_map(|value| value.as_string()).collect();
+        if strings.len() != array.len() {
+            self.warn("invalid-privacy-value", format!("{} contains values that are not strings", location));
+        }
+        Some(strings)
+    }
+
+    fn entries<'v>(&mut self, value: &'v Value, key: &str) -> Vec<(String, &'v Dictionary)> {
+        let Some(array) = value.as_array() else {
+            self.warn("invalid-privacy-value", format!("{} is not an array", key));
+            return Vec::new();
+        };
+        let mut entries = Vec::new();
+        for (position, item) in array.iter().enumerate() {
+            let location = format!("{}[{}]", key, position);
+            match item.as_dictionary() {
+                Some(dict) => entries.push((location, dict)),
+                None => self.warn("malformed-privacy-entry", format!("{} is not a dictionary and is ignored", location)),
+            }
+        }
+        entries
+    }
+
+    /// Warns about a missing or mistyped required key, which makes the whole entry unreadable.
+    fn require<'v>(&mut self, dict: &'v Dictionary, key: &str, location: &str, valid: fn(&Value) -> bool) -> Option<&'v Value> {
+        match dict.get(key) {
+            Some(value) if valid(value) => Some(value),
+            Some(_) => {
+                self.warn("malformed-privacy-entry", format!("{} of {} has the wrong type; the entry is ignored", key, location));
+                None
+            }
+            None => {
+                self.warn("malformed-privacy-entry", format!("{} is missing {}; the entry is ignored", location, key));
+                None
+            }
+        }
+    }
+
+    fn collected_data_types(&mut self, value: &Value) {
+        let mut seen = Vec::new();
+        for (location, dict) in self.entries(value, "NSPrivacyCollectedDataTypes") {
+            self.unknown_keys(dict, DATA_TYPE_KEYS, &location);
+            let data_type = self.require(dict, "NSPrivacyCollectedDataType", &location, is_string).and_then(Value::as_string);
+            self.require(dict, "NSPrivacyCollectedDataTypeLinked", &location, is_boolean);
+            self.require(dict, "NSPrivacyCollectedDataTypeTracking", &location, is_boolean);
+            let purposes = self.require(dict, "NSPrivacyCollectedDataTypePurposes", &location, is_array);
+
+            if let Some(data_type) = data_type {
+                if !DATA_TYPES.iter().any(|(known, ..)| *known == data_type) {
+                    self.warn("invalid-privacy-value", format!("{} has an unknown data type \"{}\"", location, data_type));
+                } else if seen.contains(&data_type) {
+                    self.warn("duplicate-privacy-entry", format!("{} declares {} again", location, data_type));
+                }
+                seen.push(data_type);
+            }
+            if let Some(purposes) = purposes {
+                let purposes = self.strings(purposes, &format!("NSPrivacyCollectedDataTypePurposes of {}", location)).unwrap_or_default();
+                if purposes.is_empty() {
+                    self.warn("invalid-privacy-value", format!("{} has no purpose", location));
+                }
+                for purpose in purposes.iter().filter(|purpose| !PURPOSES.iter().any(|(known, _)| known == *purpose)) {
+                    let message = if purpose.is_empty() {
+                        format!("{} has an empty purpose", location)
+                    } else {
+                        format!("{} has an unknown purpose \"{}\"", location, purpose)
+                    };
+                    self.warn("invalid-privacy-value", message);
+                }
+                for purpose in duplicates(&purposes) {
+                    self.warn("duplicate-privacy-entry", format!("{} lists purpose \"{}\" more than once", location, purpose));
+                }
+            }
+        }
+    }
+
+    fn accessed_api_types(&mut self, value: &Value) {
+        let mut seen = Vec::new();
+        for (location, dict) in self.entries(value, "NSPrivacyAccessedAPITypes") {
+            self.unknown_keys(dict, API_TYPE_KEYS, &location);
+            let category = self.require(dict, "NSPrivacyAccessedAPIType", &location, is_string).and_then(Value::as_string);
+            let reasons = self
+                .require(dict, "NSPrivacyAccessedAPITypeReasons", &location, is_array)
+                .and_then(|reasons| self.strings(reasons, &format!("NSPrivacyAccessedAPITypeReasons of {}", location)));
+
+            let approved = category.and_then(|category| {
+                let approved = API_CATEGORIES.iter().find(|(name, _)| *name == category).map(|(_, reasons)| *reasons);
+                if approved.is_none() {
+                    self.warn("invalid-privacy-value", format!("{} has an unknown API category \"{}\"", location, category));
+                } else if seen.contains(&category) {
+                    self.warn(
+                        "duplicate-privacy-entry",
+                        format!("{} declares {} again; merge its reasons into a single entry", location, category),
+                    );
+                }
+                seen.push(category);
+                approved
+            });
+            let Some(reasons) = reasons else {
+                continue;
+            };
+            if reasons.is_empty() {
+                self.warn("invalid-privacy-value", format!("{} has no reason", location));
+            }
+            if let (Some(category), Some(approved)) = (category, approved) {
+                for reason in reasons.iter().filter(|reason| !approved.contains(reason)) {
+                    self.warn(
+                        "invalid-privacy-value",
+                        format!("{} has reason \"{}\", which is not approved for {}", location, reason, category),
+                    );
+                }
+            }
+            for reason in duplicates(&reasons) {
+                self.warn("duplicate-privacy-entry", format!("{} lists reason \"{}\" more than once", location, reason));
+            }
+        }
+    }
+}
+
+/// Values listed more than once, in order of first repetition.
+fn duplicates<'v>(values: &[&'v str]) -> Vec<&'v str> {
+    let mut seen = HashSet::new();
+    let mut duplicates = Vec::new();
+    for value in values {
+        if !seen.insert(*value) && !duplicates.contains(value) {
+            duplicates.push(*value);
+        }
+    }
+    duplicates
+}
+
+fn is_string(value: &Value) -> bool {
+    value.as_string().is_some()
+}
+
+fn is_boolean(value: &Value) -> bool {
+    value.as_boolean().is_some()
+}
+
+fn is_array(value: &Value) -> bool {
+    value.as_array().is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses the body of the root dictionary of a manifest.
+    fn manifest(body: &str) -> Dictionary {
+        let xml = format!(r#"<?xml version="1.0" encoding="UTF-8"?><plist version="1.0"><dict>{}</dict></plist>"#, body);
+        plist::from_bytes(xml.as_bytes()).expect("valid plist")
+    }
+
+    fn codes(body: &str) -> Vec<String> {
+        let mut warnings = Vec::new();
+        validate_privacy_manifest(&manifest(body), "PrivacyInfo.xcprivacy", &mut warnings);
+        assert!(warnings.iter().all(|warning| warning.path.as_deref() == Some("PrivacyInfo.xcprivacy")));
+        warnings.into_iter().map(|warning| warning.code).collect()
+    }
+
+    fn data_type(purposes: &str) -> String {
+        format!(
+            "<dict><key>NSPrivacyCollectedDataType</key><string>NSPrivacyCollectedDataTypeCrashData</string>\
+             <key>NSPrivacyCollectedDataTypeLinked</key><false/>\
+             <key>NSPrivacyCollectedDataTypeTracking</key><false/>\
+             <key>NSPrivacyCollectedDataTypePurposes</key><array>{}</array></dict>",
+            purposes
+        )
+    }
+
+    fn api_type(category: &str, reasons: &str) -> String {
+        format!(
+            "<dict><key>NSPrivacyAccessedAPIType</key><string>{}</string>\
+             <key>NSPrivacyAccessedAPITypeReasons</key><array>{}</array></dict>",
+            category, reasons
+        )
+    }
+
+    #[test]
+    fn accepts_a_valid_manifest() {
+        let body = format!(
+            "<key>NSPrivacyTracking</key><true/>\
+             <key>NSPrivacyTrackingDomains</key><array><string>tracker.example.com</string></array>\
+             <key>NSPrivacyCollectedDataTypes</key><array>{}</array>\
+             <key>NSPrivacyAccessedAPITypes</key><array>{}</array>",
+            data_type("<string>NSPrivacyCollectedDataTypePurposeAnalytics</string>"),
+            api_type("NSPrivacyAccessedAPICategoryUserDefaults", "<string>CA92.1</string>"),
+        );
+        assert!(codes(&body).is_empty(), "{:?}", codes(&body));
+        assert!(codes("").is_empty());
+    }
+
+    #[test]
+    fn warns_about_empty_and_unknown_purposes() {
+        let empty = format!("<key>NSPrivacyCollectedDataTypes</key><array>{}</array>", data_type("<string></string>"));
+        assert_eq!(codes(&empty), ["invalid-privacy-value"]);
+        let unknown = format!("<key>NSPrivacyCollectedDataTypes</key><array>{}</array>", data_type("<string>Analytics</string>"));
+        assert_eq!(codes(&unknown), ["invalid-privacy-value"]);
+        let none = format!("<key>NSPrivacyCollectedDataTypes</key><array>{}</array>", data_type(""));
+        assert_eq!(codes(&none), ["invalid-privacy-value"]);
+    }
+
+    #[test]
+    fn warns_about_duplicate_entries() {
+        let user_defaults = api_type("NSPrivacyAccessedAPICategoryUserDefaults", "<string>CA92.1</string>");
+        let categories = format!("<key>NSPrivacyAccessedAPITypes</key><array>{}{}</array>", user_defaults, user_defaults);
+        assert_eq!(codes(&categories), ["duplicate-privacy-entry"]);
+
+        let reasons = format!(
+            "<key>NSPrivacyAccessedAPITypes</key><array>{}</array>",
+            api_type("NSPrivacyAccessedAPICategoryUserDefaults", "<string>CA92.1</string><string>CA92.1</string>")
+        );
+        assert_eq!(codes(&reasons), ["duplicate-privacy-entry"]);
+
+        let domains = "<key>NSPrivacyTrackingDomains</key><array><string>a.example.com</string><string>a.example.com</string></array>";
+        assert_eq!(codes(domains), ["duplicate-privacy-entry"]);
+    }
+
+    #[test]
+    fn warns_about_tracking_without_domains() {
+        assert_eq!(codes("<key>NSPrivacyTracking</key><true/>"), ["tracking-without-domains"]);
+        assert_eq!(codes("<key>NSPrivacyTracking</key><true/><key>NSPrivacyTrackingDomains</key><array/>"), ["tracking-without-domains"]);
+        assert!(codes("<key>NSPrivacyTracking</key><false/>").is_empty());
+        assert_eq!(codes("<key>NSPrivacyTracking</key><string>YES</string>"), ["invalid-privacy-value"]);
+    }
+
+    #[test]
+    fn warns_about_unknown_keys() {
+        assert_eq!(codes("<key>NSPrivacyTrackingDomain</key><array/>"), ["unknown-privacy-key"]);
+        let entry = "<dict><key>NSPrivacyAccessedAPIType</key><string>NSPrivacyAccessedAPICategoryDiskSpace</string>\
+                     <key>NSPrivacyAccessedAPITypeReasons</key><array><string>85F4.1</string></array>\
+                     <key>NSPrivacyAccessedAPITypeReason</key><string>85F4.1</string></dict>";
+        assert_eq!(codes(&format!("<key>NSPrivacyAccessedAPITypes</key><array>{}</array>", entry)), ["unknown-privacy-key"]);
+    }
+
+    #[test]
+    fn warns_about_unapproved_reasons_and_unknown_categories() {
+        let unapproved = format!(
+            "<key>NSPrivacyAccessedAPITypes</key><array>{}</array>",
+            api_type("NSPrivacyAccessedAPICategoryUserDefaults", "<string>35F9.1</string>")
+        );
+        assert_eq!(codes(&unapproved), ["invalid-privacy-value"]);
+        let unknown = format!(
+            "<key>NSPrivacyAccessedAPITypes</key><array>{}</array>",
+            api_type("NSPrivacyAccessedAPICategoryClipboard", "<string>CA92.1</string>")
+        );
+        assert_eq!(codes(&unknown), ["invalid-privacy-value"]);
+        let no_reason = format!(
+            "<key>NSPrivacyAccessedAPITypes</key><array>{}</array>",
+            api_type("NSPrivacyAccessedAPICategoryUserDefaults", "")
+        );
+        assert_eq!(codes(&no_reason), ["invalid-privacy-value"]);
+    }
+
+    #[test]
+    fn warns_about_malformed_entries() {
+        let not_a_dictionary = "<key>NSPrivacyAccessedAPITypes</key><array><string>NSPrivacyAccessedAPICategoryUserDefaults</string></array>";
+        assert_eq!(codes(not_a_dictionary), ["malformed-privacy-entry"]);
+        let not_an_array = "<key>NSPrivacyCollectedDataTypes</key><dict/>";
+        assert_eq!(codes(not_an_array), ["invalid-privacy-value"]);
+        let missing_reasons = "<key>NSPrivacyAccessedAPITypes</key><array><dict>\
+                               <key>NSPrivacyAccessedAPIType</key><string>NSPrivacyAccessedAPICategoryUserDefaults</string>\
+                               </dict></array>";
+        assert_eq!(codes(missing_reasons), ["malformed-privacy-entry"]);
+        let mistyped = "<key>NSPrivacyCollectedDataTypes</key><array><dict>\
+                        <key>NSPrivacyCollectedDataType</key><string>NSPrivacyCollectedDataTypeCrashData</string>\
+                        <key>NSPrivacyCollectedDataTypeLinked</key><string>NO</string>\
+                        <key>NSPrivacyCollectedDataTypeTracking</key><false/>\
+                        <key>NSPrivacyCollectedDataTypePurposes</key><array><string>NSPrivacyCollectedDataTypePurposeAnalytics</string></array>\
+                        </dict></array>";
+        assert_eq!(codes(mistyped), ["malformed-privacy-entry"]);
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::{BTreeSet, HashSet};
+use crate::framework_info::{PrivacyInfo, RequiredReasonApiUsage, SymbolKind, Warning};
+use crate::xcframework_processing::archive_index::{ArchiveIndex, FileKind};
+use crate::xcframework_processing::macho::ArchitectureImage;
+use crate::xcframework_processing::symbols;
+
+/// How a binary references a required-reason API.
+enum Reference {
+    /// C function, also matched with a `$` variant suffix (e.g. `_stat$INODE64`).
+    Function(&'static str),
+    /// Exact symbol name (constants and Objective-C classes).
+    Symbol(&'static str),
+    /// Symbols starting with the given text.
+    SymbolPrefix(&'static str),
+    /// Objective-C selector, which Swift also sends for imported methods and properties.
+    Selector(&'static str),
+    /// Swift member of a Foundation value type, matched on its mangled identifiers.
+    SwiftMember(&'static str, &'static str),
+    /// Any Swift symbol of the given type.
+    SwiftType(&'static str),
+}
+
+struct ApiCategory {
+    name: &'static str,
+    references: &'static [Reference],
+}
+
+/// Required-reason API categories from Apple's "Describing use of required reason API".
+/// `getattrlist` and friends read both timestamps and disk space; declaring either category covers them.
+const CATEGORIES: &[ApiCategory] = &[
+    ApiCategory {
+        name: "NSPrivacyAccessedAPICategoryFileTimestamp",
+        references: &[
+            Reference::Function("stat"),
+            Reference::Function("fstat"),
+            Reference::Function("fstatat"),
+            Reference::Function("lstat"),
+            Reference::Function("getattrlist"),
+            Reference::Function("fgetattrlist"),
+            Reference::Function("getattrlistat"),
+            Reference::Function("getattrlistbulk"),
+            Reference::Symbol("_NSFileCreationDate"),
+            Reference::Symbol("_NSFileModificationDate"),
+            Reference::Symbol("_NSURLCreationDateKey"),
+            Reference::Symbol("_NSURLContentModificationDateKey"),
+            // `NSDictionary (NSFileAttributes)` accessors.
+            Reference::Selector("fileCreationDate"),
+            Reference::Selector("fileModificationDate"),
+            Reference::SwiftMember("URLResourceValues", "creationDate"),
+            Reference::SwiftMember("URLResourceValues", "contentModificationDate"),
+        ],
+    },
+    ApiCategory {
+        name: "NSPrivacyAccessedAPICategorySystemBootTime",
+        references: &[
+            Reference::Function("mach_absolute_time"),
+            Reference::Selector("systemUptime"),
+        ],
+    },
+    ApiCategory {
+        name: "NSPrivacyAccessedAPICategoryDiskSpace",
+        references: &[
+            Reference::Function("statfs"),
+            Reference::Function("fstatfs"),
+            Reference::Function("statvfs"),
+            Reference::Function("fstatvfs"),
+            Reference::Function("getattrlist"),
+            Reference::Function("fgetattrlist"),
+            Reference::Function("getattrlistat"),
+            Reference::Symbol("_NSFileSystemFreeSize"),
+            Reference::Symbol("_NSFileSystemSize"),
+            Reference::Symbol("_NSURLVolumeAvailableCapacityKey"),
+            Reference::Symbol("_NSURLVolumeAvailableCapacityForImportantUsageKey"),
+            Reference::Symbol("_NSURLVolumeAvailableCapacityForOpportunisticUsageKey"),
+            Reference::Symbol("_NSURLVolumeTotalCapacityKey"),
+            Reference::SwiftMember("URLResourceValues", "volumeAvailableCapacity"),
+            Reference::SwiftMember("URLResourceValues", "volumeAvailableCapacityForImportantUsage"),
+            Reference::SwiftMember("URLResourceValues", "volumeAvailableCapacityForOpportunisticUsage"),
+            Reference::SwiftMember("URLResourceValues", "volumeTotalCapacity"),
+        ],
+    },
+    ApiCategory {
+        name: "NSPrivacyAccessedAPICategoryActiveKeyboards",
+        references: &[Reference::Selector("activeInputModes")],
+    },
+    ApiCategory {
+        name: "NSPrivacyAccessedAPICategoryUserDefaults",
+        references: &[
+            Reference::Symbol("_OBJC_CLASS_$_NSUserDefaults"),
+            Reference::SymbolPrefix("_CFPreferences"),
+            Reference::Selector("standardUserDefaults"),
+            Reference::SwiftType("AppStorage"),
+        ],
+    },
+];
+
+/// Compares the required-reason APIs a slice binary references (undefined symbols and
+/// Objective-C selector references) with the categories its privacy manifest declares.
+pub fn check_required_reason_apis(
+    index: &ArchiveIndex,
+    library_identifier: &str,
+    library_path: &str,
+    binary_name: &str,
+    images: &[ArchitectureImage],
+    privacy_info: Option<&PrivacyInfo>,
+    warnings: &mut Vec<Warning>,
+) -> Vec<RequiredReasonApiUsage> {
+    // Without a binary there is nothing to tell a declaration is unused.
+    if images.is_empty() {
+        return Vec::new();
+    }
+
+    let mut undefined = BTreeSet::new();
+    let mut selectors = BTreeSet::new();
+    for image in images {
+        undefined.extend(
+            symbols::image_symbols(image)
+                .into_iter()
+                .filter(|symbol| symbol.kind == SymbolKind::Undefined)
+                .map(|symbol| symbol.name),
+        );
+        for object in &image.objects {
+            selectors.extend(symbols::selector_references(object));
+        }
+    }
+
+    let declared: HashSet<&str> = privacy_info
+        .and_then(|info| info.accessed_api_types.as_ref())
+        .map(|types| types.iter().map(|api_type| api_type.api.as_str()).collect())
+        .unwrap_or_default();
+
+    let evidence: Vec<BTreeSet<String>> = CATEGORIES
+        .iter()
+        .map(|category| {
+            category
+                .references
+                .iter()
+                .flat_map(|reference| matching_references(reference, &undefined, &selectors))
+                .collect()
+        })
+        .collect();
+    // References shared with a declared category are already accounted for.
+    let covered: HashSet<&String> = CATEGORIES
+        .iter()
+        .zip(&evidence)
+        .filter(|(category, _)| declared.contains(category.name))
+        .flat_map(|(_, evidence)| evidence)
+        .collect();
+
+    let manifest_path = index
+        .library_files(library_identifier, library_path)
+        .find(|file| file.kind == FileKind::PrivacyManifest)
+        .map(|file| file.name.clone());
+
+    let mut usages = Vec::new();
+    for (category, evidence) in CATEGORIES.iter().zip(&evidence) {
+        let is_declared = declared.contains(category.name);
+        let evidence: Vec<String> = evidence
+            .iter()
+            .filter(|reference| is_declared || !covered.contains(reference))
+            .cloned()
+            .collect();
+        let used = !evidence.is_empty();
+        if !used && !is_declared {
+            continue;
+        }
+
+        if used && !is_declared {
+            warnings.push(Warning {
+                code: "undeclared-required-reason-api".to_string(),
+                message: format!(
+                    "{} references {} ({}) but does not declare it in a privacy manifest",
+                    binary_name,
+                    category.name,
+                    evidence.join(", ")
+                ),
+                path: Some(binary_name.to_string()),
+            });
+        } else if !used {
+            warnings.push(Warning {
+                code: "unused-required-reason-api".to_string(),
+                message: format!("{} is declared but {} does not reference any of its APIs", category.name, binary_name),
+                path: manifest_path.clone(),
+            });
+        }
+        usages.push(RequiredReasonApiUsage {
+            category: category.name.to_string(),
+            used,
+            declared: is_declared,
+            evidence,
+        });
+    }
+    usages
+}
+
+fn matching_references(reference: &Reference, undefined: &BTreeSet<String>, selectors: &BTreeSet<String>) -> Vec<String> {
+    match reference {
+        Reference::Function(name) => undefined
+            .iter()
+            .filter(|symbol| {
+                symbol
+                    .strip_prefix('_')
+                    .and_then(|symbol| symbol.strip_prefix(name))
+                    .is_some_and(|suffix| suffix.is_empty() || suffix.starts_with('$'))
+            })
+            .cloned()
+            .collect(),
+        Reference::Symbol(name) => undefined.iter().filter(|symbol| symbol == name).cloned().collect(),
+        Reference::SymbolPrefix(prefix) => undefined.iter().filter(|symbol| symbol.starts_with(prefix)).cloned().collect(),
+        Reference::Selector(name) => selectors
+            .iter()
+            .filter(|selector| selector == name)
+            .map(|selector| format!("@selector({})", selector))
+            .collect(),
+        Reference::SwiftMember(type_name, member) => swift_references(undefined, &[type_name, member]),
+        Reference::SwiftType(type_name) => swift_references(undefined, &[type_name]),
+    }
+}
+
+/// Swift symbols whose mangling contains every identifier, length-prefixed as in `17URLResourceValues`.
+fn swift_references(undefined: &BTreeSet<String>, identifiers: &[&str]) -> Vec<String> {
+    let mangled: Vec<String> = identifiers.iter().map(|identifier| format!("{}{}", identifier.len(), identifier)).collect();
+    undefined
+        .iter()
+        .filter(|symbol| symbol.starts_with("_$s") && mangled.iter().all(|identifier| symbol.contains(identifier.as_str())))
+        .map(|symbol| symbols::demangle(symbol).unwrap_or_else(|| symbol.clone()))
+        .collect()
+}
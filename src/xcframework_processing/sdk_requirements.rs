@@ -0,0 +1,103 @@
+use std::path::Path;
+use crate::framework_info::{FrameworkInfo, SdkRequirements, Warning};
+
+/// Apple's list of commonly used third-party SDKs, in the format of [`parse_sdk_list`].
+pub const COMMONLY_USED_SDKS: &str = include_str!("commonly_used_sdks.txt");
+
+/// Reads a list of SDK names, one per line, ignoring blank lines and `#` comments.
+pub fn parse_sdk_list(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Reads a list of SDK names from a file replacing the built-in one.
+pub fn load_sdk_list(path: &Path) -> Result<Vec<String>, String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| format!("Failed to read the SDK list: {}", e))?;
+    Ok(parse_sdk_list(&contents))
+}
+
+/// Matches a framework against the list by its name, the names of its slice libraries, their
+/// bundle identifiers (e.g. `org.cocoapods.Alamofire`) and its signing identifiers (e.g.
+/// `Alamofire.xcframework`), then checks that listed SDKs ship a privacy manifest in every slice and a valid signature.
+pub fn check_sdk_requirements(info: &FrameworkInfo, sdks: &[String], warnings: &mut Vec<Warning>) -> SdkRequirements {
+    let libraries = info.available_libraries.as_deref().unwrap_or_default();
+    let mut names: Vec<&str> = info.framework_name.as_deref().into_iter().collect();
+    for library in libraries {
+        names.push(library_name(&library.library_path));
+        if let Some(bundle_identifier) = &library.bundle_identifier {
+            names.push(bundle_identifier.rsplit('.').next().unwrap_or(bundle_identifier));
+        }
+    }
+    let signatures = info.signature.iter().chain(libraries.iter().filter_map(|library| library.signature.as_ref()));
+    for signature in signatures {
+        // The xcframework itself is signed as `Foo.xcframework`.
+        if let Some(identifier) = &signature.identifier {
+            let name = identifier.strip_suffix(".xcframework").unwrap_or(identifier);
+            names.push(name.rsplit('.').next().unwrap_or(name));
+        }
+    }
+    let sdk_name = sdks
+        .iter()
+        .find(|sdk| names.iter().any(|name| name.eq_ignore_ascii_case(sdk)))
+        .cloned();
+
+    let slices_missing_privacy_manifest: Vec<String> = libraries
+        .iter()
+        .filter(|library| !library.privacy_info.as_ref().is_some_and(|privacy_info| privacy_info.present))
+        .map(|library| library.library_identifier.clone())
+        .collect();
+    let privacy_manifest = !libraries.is_empty() && slices_missing_privacy_manifest.is_empty();
+    let signed = info.signature.as_ref().is_some_and(|signature| {
+        signature.signed && signature.verification.as_ref().is_none_or(|verification| verification.valid)
+    });
+
+    if let Some(sdk_name) = &sdk_name {
+        if !privacy_manifest {
+            warnings.push(Warning {
+                code: "missing-required-privacy-manifest".to_string(),
+                message: format!(
+                    "{} is a commonly used third-party SDK and must include a privacy manifest in every slice{}",
+                    sdk_name,
+                    if slices_missing_privacy_manifest.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" (missing in {})", slices_missing_privacy_manifest.join(", "))
+                    }
+                ),
+                path: None,
+            });
+        }
+        if !signed {
+            warnings.push(Warning {
+                code: "missing-required-signature".to_string(),
+                message: format!("{} is a commonly used third-party SDK and must be signed", sdk_name),
+                path: None,
+            });
+        }
+    }
+
+    SdkRequirements {
+        listed: sdk_name.is_some(),
+        satisfied: sdk_name.is_none() || (privacy_manifest && signed),
+        sdk_name,
+        privacy_manifest,
+        signed,
+        slices_missing_privacy_manifest,
+    }
+}
+
+/// Name of a slice library without its extension, e.g. `Alamofire` for `Alamofire.framework`
+/// and `Foo` for `libFoo.a`.
+fn library_name(library_path: &str) -> &str {
+    let file_name = library_path.rsplit('/').next().unwrap_or(library_path);
+    let stem = file_name.rsplit_once('.').map_or(file_name, |(stem, _)| stem);
+    if file_name.ends_with(".a") || file_name.ends_with(".dylib") {
+        stem.strip_prefix("lib").unwrap_or(stem)
+    } else {
+        stem
+    }
+}
@@ -0,0 +1,463 @@
+use std::collections::HashSet;
+use plist::{Dictionary, Value};
+use regex::Regex;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384};
+use der::asn1::ObjectIdentifier;
+use crate::framework_info::{SignatureVerification, Warning};
+use crate::xcframework_processing::archive_index::ArchiveIndex;
+use crate::xcframework_processing::archive_source::ArchiveSource;
+use crate::xcframework_processing::code_signature::{self, CodeDirectory, SignatureBlobs, CODE_RESOURCES, SIGNATURE_DIRECTORY};
+use crate::xcframework_processing::macho::{self, MachOFile};
+
+const SHA1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.14.3.2.26");
+const SHA256: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.1");
+const SHA384: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.2");
+
+/// Length of a cdhash, whatever the hash type of the CodeDirectory.
+const CDHASH_LENGTH: usize = 20;
+
+/// Special slots checked against the bundle (`cdInfoSlot`, `cdRequirementsSlot`, `cdResourceDirSlot`).
+const INFO_SLOT: usize = 1;
+const REQUIREMENTS_SLOT: usize = 2;
+const RESOURCES_SLOT: usize = 3;
+
+/// Verifies a signed bundle rooted at `base`: the CMS signature of a detached
+/// CodeDirectory, the special slots, and every file sealed in `CodeResources`.
+///
+/// `executable` is the main binary of the bundle, sealed by the CodeDirectory
+/// page hashes (see [`verify_code`]) rather than by `CodeResources`.
+pub fn verify_bundle(
+    archive: &mut dyn ArchiveSource,
+    index: &ArchiveIndex,
+    base: &str,
+    blobs: &SignatureBlobs,
+    executable: Option<&str>,
+) -> SignatureVerification {
+    let mut verification = SignatureVerification::default();
+    if executable.is_none() {
+        verify_cms(blobs, &format!("{}CodeDirectory", SIGNATURE_DIRECTORY), &mut verification);
+    }
+    let Some(code_directory) = blobs.code_directory() else {
+        return verification;
+    };
+
+    let resources_path = format!("{}{}", SIGNATURE_DIRECTORY, CODE_RESOURCES);
+    let resources = read(archive, index, &format!("{}{}", base, resources_path));
+    // macOS frameworks keep their Info.plist under `Versions/A/Resources`.
+    let info_path = ["Info.plist", "Resources/Info.plist"]
+        .into_iter()
+        .find(|path| index.get(&format!("{}{}", base, path)).is_some())
+        .unwrap_or("Info.plist");
+    let info = read(archive, index, &format!("{}{}", base, info_path));
+
+    check_special_slot(code_directory, INFO_SLOT, info.as_deref(), info_path, &mut verification);
+    check_special_slot(
+        code_directory,
+        REQUIREMENTS_SLOT,
+        blobs.requirements_data.as_deref(),
+        &format!("{}CodeRequirements", SIGNATURE_DIRECTORY),
+        &mut verification,
+    );
+    check_special_slot(code_directory, RESOURCES_SLOT, resources.as_deref(), &resources_path, &mut verification);
+
+    if let Some(resources) = resources {
+        let executable = executable.map(|name| name.strip_prefix(base).unwrap_or(name));
+        verify_resources(archive, index, base, &resources, executable, &mut verification);
+    }
+    verification
+}
+
+/// Recomputes the page hashes of every CodeDirectory embedded in a Mach-O file,
+/// and checks that its CMS signature was made over one of them.
+pub fn verify_code(file: &MachOFile, blobs: &SignatureBlobs, label: &str, verification: &mut SignatureVerification) {
+    verify_cms(blobs, label, verification);
+
+    for code_directory in &blobs.code_directories {
+        let limit = (code_directory.code_limit as usize).min(file.bytes.len());
+        let code = &file.bytes[..limit];
+        let page_size = match code_directory.page_size {
+            0 => limit.max(1),
+            page_size => page_size,
+        };
+
+        let intact = code_directory.code_slots.len() == limit.div_ceil(page_size)
+            && code_directory.code_slots.iter().enumerate().all(|(page, expected)| {
+                let start = page * page_size;
+                let end = (start + page_size).min(limit);
+                matches_hash(expected, code_directory.hash_type, &code[start..end])
+            });
+        if !intact {
+            push_unique(&mut verification.tampered, label);
+        }
+    }
+}
+
+/// Marks the verification as valid when nothing was found, and warns otherwise.
+pub fn finish(verification: &mut SignatureVerification, path: &str, warnings: &mut Vec<Warning>) {
+    verification.valid = verification.tampered.is_empty() && verification.missing.is_empty() && verification.extra.is_empty();
+    if !verification.valid {
+        warnings.push(Warning {
+            code: "signature-tampered".to_string(),
+            message: format!(
+                "Signature does not match the content: {} tampered, {} missing and {} extra file(s)",
+                verification.tampered.len(),
+                verification.missing.len(),
+                verification.extra.len()
+            ),
+            path: Some(path.to_string()),
+        });
+    }
+}
+
+/// Hashes a blob as a CodeDirectory of the given `CS_HASHTYPE_*` does.
+pub fn digest(hash_type: u8, bytes: &[u8]) -> Option<Vec<u8>> {
+    match hash_type {
+        1 => Some(Sha1::digest(bytes).to_vec()),
+        // Truncated SHA-256 only keeps the first 20 bytes, compared as a prefix below.
+        2 | 3 => Some(Sha256::digest(bytes).to_vec()),
+        4 => Some(Sha384::digest(bytes).to_vec()),
+        _ => None,
+    }
+}
+
+/// Returns the cdhash of a CodeDirectory, as recorded for nested code in `CodeResources`.
+pub fn cdhash(code_directory: &CodeDirectory) -> Option<Vec<u8>> {
+    let mut hash = digest(code_directory.hash_type, &code_directory.raw)?;
+    hash.truncate(CDHASH_LENGTH);
+    Some(hash)
+}
+
+fn matches_hash(expected: &[u8], hash_type: u8, bytes: &[u8]) -> bool {
+    digest(hash_type, bytes).is_some_and(|hash| hash.len() >= expected.len() && hash[..expected.len()] == *expected)
+}
+
+fn read(archive: &mut dyn ArchiveSource, index: &ArchiveIndex, name: &str) -> Option<Vec<u8>> {
+    index.get(name)?;
+    archive.read_file(name).ok()
+}
+
+fn push_unique(list: &mut Vec<String>, value: &str) {
+    if !list.iter().any(|existing| existing == value) {
+        list.push(value.to_string());
+    }
+}
+
+/// Checks that the CMS `messageDigest` matches one of the CodeDirectories, so that
+/// hashes rewritten after signing are caught even when they match the content.
+fn verify_cms(blobs: &SignatureBlobs, label: &str, verification: &mut SignatureVerification) {
+    let Some(cms) = blobs.cms.as_deref().filter(|cms| !cms.is_empty()) else {
+        return;
+    };
+    // Malformed CMS blobs are already reported as invalid signatures.
+    let Ok(Some((algorithm, expected))) = code_signature::message_digest(cms) else {
+        return;
+    };
+    let hash_type = match algorithm {
+        SHA1 => 1,
+        SHA256 => 2,
+        SHA384 => 4,
+        _ => return,
+    };
+    if !blobs
+        .code_directories
+        .iter()
+        .any(|code_directory| digest(hash_type, &code_directory.raw).as_deref() == Some(expected.as_slice()))
+    {
+        push_unique(&mut verification.tampered, label);
+    }
+}
+
+fn check_special_slot(
+    code_directory: &CodeDirectory,
+    slot: usize,
+    content: Option<&[u8]>,
+    label: &str,
+    verification: &mut SignatureVerification,
+) {
+    // A zeroed slot means nothing was bound to it.
+    let Some(expected) = code_directory.special_slots.get(slot - 1).filter(|hash| hash.iter().any(|&byte| byte != 0)) else {
+        return;
+    };
+    match content {
+        Some(content) if !matches_hash(expected, code_directory.hash_type, content) => push_unique(&mut verification.tampered, label),
+        Some(_) => {}
+        None => push_unique(&mut verification.missing, label),
+    }
+}
+
+/// A resource rule of `CodeResources`: which files are sealed, omitted or nested code.
+struct ResourceRule {
+    pattern: Regex,
+    omit: bool,
+    nested: bool,
+    weight: f64,
+}
+
+/// A sealed file of `CodeResources`.
+struct SealedFile {
+    path: String,
+    /// `(hash type, hash)`, absent for symbolic links.
+    hash: Option<(u8, Vec<u8>)>,
+    optional: bool,
+    /// cdhash of nested code (a framework or bundle sealed as a whole).
+    cdhash: Option<Vec<u8>>,
+}
+
+fn verify_resources(
+    archive: &mut dyn ArchiveSource,
+    index: &ArchiveIndex,
+    base: &str,
+    resources: &[u8],
+    executable: Option<&str>,
+    verification: &mut SignatureVerification,
+) {
+    let Some(Value::Dictionary(dict)) = plist::from_bytes::<Value>(resources).ok() else {
+        push_unique(&mut verification.tampered, &format!("{}{}", SIGNATURE_DIRECTORY, CODE_RESOURCES));
+        return;
+    };
+    // `files2`/`rules2` supersede the SHA-1 only `files`/`rules` of older signatures.
+    let (files, rules) = match dict.get("files2") {
+        Some(files) => (files.as_dictionary(), dict.get("rules2")),
+        None => (dict.get("files").and_then(|files| files.as_dictionary()), dict.get("rules")),
+    };
+    let sealed: Vec<SealedFile> = files.map(sealed_files).unwrap_or_default();
+    let rules = rules.and_then(|rules| rules.as_dictionary()).map(resource_rules);
+
+    for file in &sealed {
+        let name = format!("{}{}", base, file.path);
+        if let Some(expected) = &file.cdhash {
+            let present = index.get(&name).is_some() || index.files_under(&format!("{}/", name)).next().is_some();
+            if !present {
+                if !file.optional {
+                    verification.missing.push(file.path.clone());
+                }
+            } else if !nested_cdhashes(archive, index, &name).contains(expected) {
+                verification.tampered.push(file.path.clone());
+            }
+            continue;
+        }
+
+        if index.get(&name).is_none() {
+            if !file.optional {
+                verification.missing.push(file.path.clone());
+            }
+            continue;
+        }
+        if let Some((hash_type, expected)) = &file.hash {
+            match archive.read_file(&name) {
+                Ok(content) if matches_hash(expected, *hash_type, &content) => {}
+                _ => verification.tampered.push(file.path.clone()),
+            }
+        }
+    }
+
+    let sealed_paths: HashSet<&str> = sealed.iter().map(|file| file.path.as_str()).collect();
+    let nested: HashSet<&str> = sealed.iter().filter(|file| file.cdhash.is_some()).map(|file| file.path.as_str()).collect();
+    for file in index.files_under(base) {
+        let path = &file.name[base.len()..];
+        // Files inside nested code are sealed by its own signature.
+        let inside_nested = path.match_indices('/').any(|(position, _)| nested.contains(&path[..position]));
+        if path.starts_with(SIGNATURE_DIRECTORY) || Some(path) == executable || sealed_paths.contains(path) || inside_nested {
+            continue;
+        }
+        let sealed_by_rules = match &rules {
+            Some(rules) => rules
+                .iter()
+                .filter(|rule| rule.pattern.is_match(path))
+                .max_by(|a, b| a.weight.total_cmp(&b.weight))
+                .is_some_and(|rule| !rule.omit && !rule.nested),
+            None => true,
+        };
+        if sealed_by_rules {
+            verification.extra.push(path.to_string());
+        }
+    }
+}
+
+fn sealed_files(files: &Dictionary) -> Vec<SealedFile> {
+    files
+        .iter()
+        .map(|(path, entry)| {
+            let mut sealed = SealedFile { path: path.clone(), hash: None, optional: false, cdhash: None };
+            match entry {
+                Value::Data(hash) => sealed.hash = Some((1, hash.clone())),
+                Value::Dictionary(entry) => {
+                    let data = |key: &str| entry.get(key).and_then(|value| value.as_data()).map(|data| data.to_vec());
+                    sealed.hash = data("hash2").map(|hash| (2, hash)).or_else(|| data("hash").map(|hash| (1, hash)));
+                    sealed.optional = entry.get("optional").and_then(|value| value.as_boolean()).unwrap_or(false);
+                    sealed.cdhash = data("cdhash");
+                }
+                _ => {}
+            }
+            sealed
+        })
+        .collect()
+}
+
+fn resource_rules(rules: &Dictionary) -> Vec<ResourceRule> {
+    rules
+        .iter()
+        .filter_map(|(pattern, rule)| {
+            let pattern = Regex::new(pattern).ok()?;
+            let flag = |key: &str| rule.as_dictionary().and_then(|rule| rule.get(key)).and_then(|value| value.as_boolean()).unwrap_or(false);
+            let weight = rule
+                .as_dictionary()
+                .and_then(|rule| rule.get("weight"))
+                .and_then(|value| value.as_real().or_else(|| value.as_signed_integer().map(|weight| weight as f64)))
+                .unwrap_or(1.0);
+            Some(ResourceRule { pattern, omit: flag("omit"), nested: flag("nested"), weight })
+        })
+        .collect()
+}
+
+/// Returns the cdhashes of nested code: its detached signature if it has one,
+/// otherwise the signature embedded in its executable (named after the bundle).
+fn nested_cdhashes(archive: &mut dyn ArchiveSource, index: &ArchiveIndex, name: &str) -> Vec<Vec<u8>> {
+    let directory = format!("{}/{}", name, SIGNATURE_DIRECTORY);
+    if let Ok(Some(blobs)) = code_signature::read_detached(archive, index, &directory) {
+        return blobs.code_directories.iter().filter_map(cdhash).collect();
+    }
+
+    let bundle = name.rsplit('/').next().unwrap_or(name);
+    let stem = bundle.rsplit_once('.').map_or(bundle, |(stem, _)| stem);
+    let executable = [format!("{}/{}", name, stem), format!("{}/Versions/A/{}", name, stem), name.to_string()]
+        .into_iter()
+        .find(|executable| index.get(executable).is_some());
+    let Some(bytes) = executable.and_then(|executable| archive.read_file(&executable).ok()) else {
+        return Vec::new();
+    };
+    macho::parse_images(&bytes)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|image| image.objects.first())
+        .filter_map(|file| code_signature::read_embedded(file).ok().flatten())
+        .flat_map(|blobs| blobs.code_directories.iter().filter_map(cdhash).collect::<Vec<_>>())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xcframework_processing::archive_source::MemorySource;
+
+    fn dictionary(entries: Vec<(&str, Value)>) -> Dictionary {
+        entries.into_iter().map(|(key, value)| (key.to_string(), value)).collect()
+    }
+
+    fn sha256(content: &[u8]) -> Value {
+        Value::Data(Sha256::digest(content).to_vec())
+    }
+
+    fn code_resources(entries: Vec<(&str, Value)>) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        plist::to_writer_xml(&mut buffer, &Value::Dictionary(dictionary(entries))).unwrap();
+        buffer
+    }
+
+    /// Verifies the resources of `Foo.framework/` sealed by `resources` against `files`.
+    fn verify(files: &[(&str, &[u8])], resources: &[u8]) -> SignatureVerification {
+        let mut source = MemorySource::new(files.iter().map(|(name, content)| (format!("Foo.framework/{}", name), content.to_vec())));
+        let index = ArchiveIndex::build(source.entries().unwrap());
+        let mut verification = SignatureVerification::default();
+        verify_resources(&mut source, &index, "Foo.framework/", resources, Some("Foo"), &mut verification);
+        verification
+    }
+
+    #[test]
+    fn parses_sealed_files() {
+        let files = dictionary(vec![
+            ("a", Value::Data(vec![1; 20])),
+            ("b", Value::Dictionary(dictionary(vec![("hash", Value::Data(vec![2; 20])), ("hash2", Value::Data(vec![3; 32]))]))),
+            ("c", Value::Dictionary(dictionary(vec![("hash2", Value::Data(vec![4; 32])), ("optional", Value::Boolean(true))]))),
+            ("d", Value::Dictionary(dictionary(vec![("cdhash", Value::Data(vec![5; 20]))]))),
+        ]);
+        let sealed = sealed_files(&files);
+        assert_eq!(sealed[0].hash, Some((1, vec![1; 20])));
+        assert_eq!(sealed[1].hash, Some((2, vec![3; 32])));
+        assert!(!sealed[1].optional);
+        assert!(sealed[2].optional);
+        assert_eq!(sealed[3].hash, None);
+        assert_eq!(sealed[3].cdhash, Some(vec![5; 20]));
+    }
+
+    #[test]
+    fn parses_resource_rules() {
+        let rules = dictionary(vec![
+            ("^.*", Value::Boolean(true)),
+            ("^.*\\.lproj/", Value::Dictionary(dictionary(vec![("optional", Value::Boolean(true)), ("weight", Value::Real(1000.0))]))),
+            ("^Docs/", Value::Dictionary(dictionary(vec![("omit", Value::Boolean(true)), ("weight", Value::Integer(20.into()))]))),
+            ("^[^/]+\\.framework/", Value::Dictionary(dictionary(vec![("nested", Value::Boolean(true))]))),
+            ("(unclosed", Value::Boolean(true)),
+        ]);
+        let rules = resource_rules(&rules);
+        assert_eq!(rules.len(), 4);
+        let weights: Vec<f64> = rules.iter().map(|rule| rule.weight).collect();
+        assert_eq!(weights, [1.0, 1000.0, 20.0, 1.0]);
+        assert!(rules[2].omit && !rules[2].nested);
+        assert!(rules[3].nested && !rules[3].omit);
+    }
+
+    #[test]
+    fn reports_tampered_missing_and_extra_files() {
+        let resources = code_resources(vec![
+            ("files", Value::Dictionary(dictionary(vec![("ignored", Value::Data(vec![0; 20]))]))),
+            (
+                "files2",
+                Value::Dictionary(dictionary(vec![
+                    ("intact", Value::Dictionary(dictionary(vec![("hash2", sha256(b"intact"))]))),
+                    ("changed", Value::Dictionary(dictionary(vec![("hash2", sha256(b"original"))]))),
+                    ("removed", Value::Dictionary(dictionary(vec![("hash2", sha256(b"removed"))]))),
+                    ("optional", Value::Dictionary(dictionary(vec![("hash2", sha256(b"optional")), ("optional", Value::Boolean(true))]))),
+                ])),
+            ),
+            (
+                "rules2",
+                Value::Dictionary(dictionary(vec![
+                    ("^.*", Value::Boolean(true)),
+                    ("^Docs/", Value::Dictionary(dictionary(vec![("omit", Value::Boolean(true)), ("weight", Value::Integer(20.into()))]))),
+                ])),
+            ),
+        ]);
+        let verification = verify(
+            &[
+                ("Foo", b"binary"),
+                ("_CodeSignature/CodeResources", &resources),
+                ("intact", b"intact"),
+                ("changed", b"changed"),
+                ("added", b"added"),
+                ("Docs/readme", b"omitted"),
+            ],
+            &resources,
+        );
+        assert_eq!(verification.tampered, ["changed"]);
+        assert_eq!(verification.missing, ["removed"]);
+        assert_eq!(verification.extra, ["added"]);
+    }
+
+    #[test]
+    fn falls_back_to_sha1_files() {
+        let resources = code_resources(vec![(
+            "files",
+            Value::Dictionary(dictionary(vec![("intact", Value::Data(Sha1::digest(b"intact").to_vec()))])),
+        )]);
+        let verification = verify(&[("intact", b"intact")], &resources);
+        assert!(verification.tampered.is_empty() && verification.missing.is_empty() && verification.extra.is_empty());
+    }
+
+    #[test]
+    fn checks_nested_code_as_a_whole() {
+        let resources = code_resources(vec![(
+            "files2",
+            Value::Dictionary(dictionary(vec![
+                ("Frameworks/Bar.framework", Value::Dictionary(dictionary(vec![("cdhash", Value::Data(vec![1; 20]))]))),
+                ("Frameworks/Baz.framework", Value::Dictionary(dictionary(vec![("cdhash", Value::Data(vec![2; 20]))]))),
+            ])),
+        )]);
+        let verification = verify(&[("Frameworks/Bar.framework/Bar", b"unsigned"), ("Frameworks/Bar.framework/Info.plist", b"")], &resources);
+        // Bar has no signature matching its cdhash; its files are not extra. Baz is missing.
+        assert_eq!(verification.tampered, ["Frameworks/Bar.framework"]);
+        assert_eq!(verification.missing, ["Frameworks/Baz.framework"]);
+        assert!(verification.extra.is_empty());
+    }
+}
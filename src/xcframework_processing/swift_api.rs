@@ -0,0 +1,803 @@
+use crate::framework_info::{ApiDeclaration, DeclarationKind, LibraryInfo};
+use crate::xcframework_processing::archive_index::ArchiveIndex;
+use crate::xcframework_processing::archive_source::ArchiveSource;
+
+/// Modifiers that may precede the introducer of a declaration.
+const MODIFIERS: &[&str] = &[
+    "public", "open", "package", "internal", "private", "fileprivate", "final", "static", "class", "override",
+    "required", "convenience", "mutating", "nonmutating", "dynamic", "optional", "indirect", "lazy", "weak",
+    "unowned", "nonisolated", "distributed", "prefix", "postfix", "infix", "consuming", "borrowing", "__consuming",
+];
+
+/// Accessors reported in the signature of properties and subscripts.
+const ACCESSORS: &[&str] = &["get", "set", "_read", "_modify", "unsafeAddress", "unsafeMutableAddress"];
+
+/// Builds the public API inventory of a framework from the public `.swiftinterface`
+/// of every slice. Declarations found in every slice are listed once, the others
+/// record which slices declare them.
+pub fn extract_api(archive: &mut dyn ArchiveSource, index: &ArchiveIndex, libraries: &[LibraryInfo]) -> Vec<ApiDeclaration> {
+    let mut declarations: Vec<ApiDeclaration> = Vec::new();
+    let mut slices = Vec::new();
+
+    for library in libraries {
+        // Every architecture of a slice usually declares the same API; merging them covers the exceptions.
+        for interface in &library.swift_interfaces {
+            let name = index.slice_path(&library.library_identifier, &interface.path);
+            let Ok(contents) = archive.read_to_string(&name) else {
+                continue;
+            };
+            let module_name = interface.flags.module_name.as_deref().unwrap_or_default();
+            for declaration in parse_interface(&contents, module_name) {
+                let existing = declarations.iter_mut().find(|existing| {
+                    existing.kind == declaration.kind && existing.name == declaration.name && existing.signature == declaration.signature
+                });
+                let identifiers = match existing {
+                    Some(existing) => &mut existing.library_identifiers,
+                    None => {
+                        declarations.push(declaration);
+                        &mut declarations.last_mut().expect("declaration was just pushed").library_identifiers
+                    }
+                };
+                if !identifiers.contains(&library.library_identifier) {
+                    identifiers.push(library.library_identifier.clone());
+                }
+            }
+            if !slices.contains(&library.library_identifier) {
+                slices.push(library.library_identifier.clone());
+            }
+        }
+    }
+
+    for declaration in &mut declarations {
+        if declaration.library_identifiers.len() == slices.len() {
+            declaration.library_identifiers.clear();
+        }
+    }
+    declarations
+}
+
+/// Lists the public and open declarations of a `.swiftinterface`.
+///
+/// Interfaces are generated by the compiler, so declarations are expected one per
+/// line, with attributes either on the same line or on the lines before it.
+/// Only the first branch of `#if` blocks is read.
+pub fn parse_interface(contents: &str, module_name: &str) -> Vec<ApiDeclaration> {
+    let source = strip_conditional_branches(contents);
+    let mut parser = Parser {
+        module_prefix: format!("{}.", module_name),
+        scopes: Vec::new(),
+        pending_attributes: String::new(),
+        declarations: Vec::new(),
+    };
+    parser.run(source.as_bytes());
+    parser.declarations
+}
+
+/// Keeps the first branch of every `#if`/`#elseif`/`#else` block.
+fn strip_conditional_branches(contents: &str) -> String {
+    // One entry per open block: whether the current branch is kept.
+    let mut blocks: Vec<bool> = Vec::new();
+    let mut output = String::with_capacity(contents.len());
+    for line in contents.lines() {
+        let trimmed = line.trim_start();
+        let parent_kept = blocks.iter().all(|&kept| kept);
+        if trimmed.starts_with("#if") {
+            blocks.push(true);
+        } else if trimmed.starts_with("#elseif") || trimmed.starts_with("#else") {
+            if let Some(kept) = blocks.last_mut() {
+                *kept = false;
+            }
+        } else if trimmed.starts_with("#endif") {
+            blocks.pop();
+        } else if parent_kept {
+            output.push_str(line);
+            output.push('\n');
+        }
+    }
+    output
+}
+
+/// Type or extension whose body is being read.
+struct Scope {
+    /// Qualified name of the type, `None` for scopes whose members are not reported.
+    name: Option<String>,
+    kind: Option<DeclarationKind>,
+}
+
+struct Parser {
+    module_prefix: String,
+    scopes: Vec<Scope>,
+    /// Attributes written on their own lines, applied to the next declaration.
+    pending_attributes: String,
+    declarations: Vec<ApiDeclaration>,
+}
+
+impl Parser {
+    fn run(&mut self, bytes: &[u8]) {
+        let mut current: Vec<u8> = Vec::new();
+        let mut depth = 0usize;
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'"' => {
+                    let end = string_end(bytes, i);
+                    current.extend_from_slice(&bytes[i..end]);
+                    i = end;
+                    continue;
+                }
+                b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                    i = bytes[i..].iter().position(|&byte| byte == b'\n').map_or(bytes.len(), |end| i + end);
+                    continue;
+                }
+                b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                    i = find(bytes, i + 2, b"*/").map_or(bytes.len(), |end| end + 2);
+                    continue;
+                }
+                b'(' | b'[' => depth += 1,
+                b')' | b']' => depth = depth.saturating_sub(1),
+                b'\n' | b';' if depth == 0 => {
+                    self.line(&take(&mut current));
+                    i += 1;
+                    continue;
+                }
+                b'{' if depth == 0 => {
+                    let header = take(&mut current);
+                    let (declaration, container) = self.declare(&header, true);
+                    if !container {
+                        let end = block_end(bytes, i);
+                        if let Some(position) = declaration {
+                            append_accessors(&mut self.declarations[position], &bytes[i + 1..end.saturating_sub(1)]);
+                        }
+                        i = end;
+                        continue;
+                    }
+                    i += 1;
+                    continue;
+                }
+                b'}' if depth == 0 => {
+                    self.line(&take(&mut current));
+                    self.scopes.pop();
+                    i += 1;
+                    continue;
+                }
+                _ => {}
+            }
+            current.push(bytes[i]);
+            i += 1;
+        }
+        self.line(&take(&mut current));
+    }
+
+    fn line(&mut self, text: &str) {
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        let (attributes, rest) = split_attributes(text);
+        if rest.is_empty() && !attributes.is_empty() {
+            self.pending_attributes.push_str(text);
+            self.pending_attributes.push(' ');
+            return;
+        }
+        self.declare(text, false);
+    }
+
+    /// Records a declaration (if public) and, for types and extensions with a body, opens
+    /// their scope. Returns the position of the declaration and whether its body holds members.
+    fn declare(&mut self, header: &str, has_body: bool) -> (Option<usize>, bool) {
+        let text = format!("{}{}", take_string(&mut self.pending_attributes), header.trim());
+        let parent = self.scopes.last();
+        let parent_name = parent.and_then(|scope| scope.name.clone());
+        let parent_kind = parent.and_then(|scope| scope.kind);
+        // Members of unreported scopes are not reported either.
+        let hidden = parent.is_some_and(|scope| scope.name.is_none());
+
+        let Some(header) = parse_header(&text) else {
+            // Unknown bodies are still scopes, so that their closing brace is matched.
+            if has_body {
+                self.scopes.push(Scope { name: None, kind: None });
+            }
+            return (None, has_body);
+        };
+        let container = has_body && matches!(
+            header.kind,
+            DeclarationKind::Class
+                | DeclarationKind::Struct
+                | DeclarationKind::Enum
+                | DeclarationKind::Actor
+                | DeclarationKind::Protocol
+                | DeclarationKind::Extension
+        );
+
+        let public = !hidden
+            && match header.access {
+                Some("public") | Some("open") => true,
+                Some(_) => false,
+                // Protocol requirements and enum cases take the access of their parent;
+                // operators and extensions have none of their own.
+                None => {
+                    parent_kind == Some(DeclarationKind::Protocol)
+                        || header.kind == DeclarationKind::Case
+                        || matches!(
+                            header.kind,
+                            DeclarationKind::Extension | DeclarationKind::Operator | DeclarationKind::PrecedenceGroup
+                        )
+                }
+            };
+
+        let name = match header.kind {
+            DeclarationKind::Extension => {
+                let extended = header.name.strip_prefix(&self.module_prefix).unwrap_or(&header.name);
+                extended.split('<').next().unwrap_or(extended).to_string()
+            }
+            _ => match &parent_name {
+                Some(parent) => format!("{}.{}", parent, header.name),
+                None => header.name.clone(),
+            },
+        };
+
+        if container {
+            self.scopes.push(Scope { name: public.then(|| name.clone()), kind: Some(header.kind) });
+        }
+        // Extensions only add API through their members, unless they add conformances.
+        let reported = public && (header.kind != DeclarationKind::Extension || header.conformances);
+        if !reported {
+            return (None, container);
+        }
+
+        // `case a, b(Int)` declares several cases.
+        let names = match header.kind {
+            DeclarationKind::Case => header.cases.clone(),
+            _ => vec![(name, header.signature.clone())],
+        };
+        let mut position = None;
+        for (name, signature) in names {
+            let name = match (header.kind, &parent_name) {
+                (DeclarationKind::Case, Some(parent)) => format!("{}.{}", parent, name),
+                _ => name,
+            };
+            position = Some(self.declarations.len());
+            self.declarations.push(ApiDeclaration {
+                kind: header.kind,
+                name,
+                signature,
+                container: parent_kind,
+                generic_signature: header.generic_signature.clone(),
+                availability: header.availability.clone(),
+                attributes: header.attributes.clone(),
+                library_identifiers: Vec::new(),
+            });
+        }
+        (position, container)
+    }
+}
+
+struct Header {
+    kind: DeclarationKind,
+    access: Option<&'static str>,
+    name: String,
+    signature: String,
+    generic_signature: Option<String>,
+    availability: Vec<String>,
+    attributes: Vec<String>,
+    /// `(name, signature)` of each case of a `case` declaration.
+    cases: Vec<(String, String)>,
+    /// Whether an extension declares conformances.
+    conformances: bool,
+}
+
+fn parse_header(text: &str) -> Option<Header> {
+    let (all_attributes, mut rest) = split_attributes(text);
+    let (availability, attributes): (Vec<String>, Vec<String>) =
+        all_attributes.into_iter().partition(|attribute| attribute.starts_with("@available"));
+
+    let mut modifiers = Vec::new();
+    let mut access = None;
+    loop {
+        let word = leading_word(rest);
+        // `private(set)` and friends restrict the setter only.
+        let is_setter_access = rest[word.len()..].starts_with("(set)");
+        let length = if is_setter_access { word.len() + "(set)".len() } else { word.len() };
+        // `class` is a modifier only when another introducer follows it.
+        let is_class_modifier = word == "class" && matches!(leading_word(rest[length..].trim_start()), "func" | "var" | "let" | "subscript");
+        if !MODIFIERS.contains(&word) || (word == "class" && !is_class_modifier) {
+            break;
+        }
+        if !is_setter_access && matches!(word, "public" | "open" | "package" | "internal" | "private" | "fileprivate") {
+            access = MODIFIERS.iter().copied().find(|modifier| *modifier == word);
+        }
+        modifiers.push(&rest[..length]);
+        rest = rest[length..].trim_start();
+    }
+
+    let introducer = leading_word(rest);
+    let kind = match introducer {
+        "class" => DeclarationKind::Class,
+        "struct" => DeclarationKind::Struct,
+        "enum" => DeclarationKind::Enum,
+        "actor" => DeclarationKind::Actor,
+        "protocol" => DeclarationKind::Protocol,
+        "extension" => DeclarationKind::Extension,
+        "func" => DeclarationKind::Func,
+        "init" => DeclarationKind::Initializer,
+        "subscript" => DeclarationKind::Subscript,
+        "var" => DeclarationKind::Var,
+        "let" => DeclarationKind::Let,
+        "typealias" => DeclarationKind::Typealias,
+        "associatedtype" => DeclarationKind::AssociatedType,
+        "case" => DeclarationKind::Case,
+        "macro" => DeclarationKind::Macro,
+        "operator" => DeclarationKind::Operator,
+        "precedencegroup" => DeclarationKind::PrecedenceGroup,
+        // `import`, `deinit` and anything unexpected.
+        _ => return None,
+    };
+    let tail = rest[introducer.len()..].trim();
+    // `init?(...)`, `init<T>(...)` and `subscript(...)` are written without a space.
+    let attached = matches!(kind, DeclarationKind::Initializer | DeclarationKind::Subscript) && tail.starts_with(['(', '?', '!', '<']);
+    let separator = if attached { "" } else { " " };
+    let signature = collapse_whitespace(&format!("{} {}{}{}", modifiers.join(" "), introducer, separator, tail));
+
+    let mut conformances = false;
+    let (name, generic_signature, cases) = match kind {
+        DeclarationKind::Extension => {
+            let end = find_top_level(tail, &[":", " where "]).unwrap_or(tail.len());
+            conformances = tail[end..].starts_with(':');
+            (tail[..end].trim().to_string(), where_clause(tail).map(|clause| clause.to_string()), Vec::new())
+        }
+        DeclarationKind::Func | DeclarationKind::Macro | DeclarationKind::Initializer | DeclarationKind::Subscript => {
+            let base = match kind {
+                DeclarationKind::Initializer => "init",
+                DeclarationKind::Subscript => "subscript",
+                _ => "",
+            };
+            // Operator functions (`static func < (lhs: ...)`) are named by their symbol.
+            let name_end = if tail.starts_with(|character: char| character.is_alphanumeric() || character == '_' || character == '`') {
+                tail.find(['<', '(']).unwrap_or(tail.len())
+            } else {
+                tail.find([' ', '(']).unwrap_or(tail.len())
+            };
+            let name = format!("{}{}", base, tail[..name_end].trim().trim_start_matches(['?', '!']));
+            let after_name = &tail[name_end..];
+            let generics = generic_parameters(after_name);
+            let parameters_start = generics.map_or(0, |generics| generics.len());
+            let labels = parameter_list(&after_name[parameters_start..])
+                .map(|parameters| argument_labels(parameters, kind == DeclarationKind::Subscript))
+                .unwrap_or_default();
+            let name = if kind == DeclarationKind::Macro || !labels.is_empty() || after_name[parameters_start..].starts_with('(') {
+                format!("{}({})", name, labels)
+            } else {
+                name
+            };
+            (name, generic_signature(generics, where_clause(tail)), Vec::new())
+        }
+        DeclarationKind::Case => {
+            let cases: Vec<(String, String)> = split_top_level(tail, ',')
+                .into_iter()
+                .map(|case| {
+                    let case = case.trim();
+                    let name_end = case.find(['(', '=', ' ']).unwrap_or(case.len());
+                    let name = match parameter_list(&case[name_end..]) {
+                        Some(parameters) => format!("{}({})", &case[..name_end], case_labels(parameters)),
+                        None => case[..name_end].to_string(),
+                    };
+                    (name, collapse_whitespace(&format!("case {}", case)))
+                })
+                .collect();
+            let name = cases.first().map(|(name, _)| name.clone()).unwrap_or_default();
+            (name, None, cases)
+        }
+        DeclarationKind::Operator | DeclarationKind::PrecedenceGroup => {
+            (tail.split_whitespace().next().unwrap_or_default().to_string(), None, Vec::new())
+        }
+        _ => {
+            let name_end = tail.find(['<', ':', '=', ' ', '{']).unwrap_or(tail.len());
+            let generics = generic_parameters(&tail[name_end..]);
+            (tail[..name_end].to_string(), generic_signature(generics, where_clause(tail)), Vec::new())
+        }
+    };
+
+    Some(Header {
+        kind,
+        access,
+        name: name.trim_matches('`').to_string(),
+        signature,
+        generic_signature,
+        availability,
+        attributes,
+        cases,
+        conformances,
+    })
+}
+
+/// Splits the leading `@attribute(...)` list from the rest of a declaration.
+fn split_attributes(text: &str) -> (Vec<String>, &str) {
+    let mut attributes = Vec::new();
+    let mut rest = text.trim_start();
+    while rest.starts_with('@') {
+        let bytes = rest.as_bytes();
+        let mut end = 1;
+        while end < bytes.len() && (bytes[end].is_ascii_alphanumeric() || bytes[end] == b'_' || bytes[end] == b'.') {
+            end += 1;
+        }
+        if bytes.get(end) == Some(&b'(') {
+            end = balanced_end(bytes, end, b'(', b')');
+        }
+        attributes.push(collapse_whitespace(&rest[..end]));
+        rest = rest[end..].trim_start();
+    }
+    (attributes, rest)
+}
+
+fn leading_word(text: &str) -> &str {
+    let end = text
+        .find(|character: char| !(character.is_alphanumeric() || character == '_'))
+        .unwrap_or(text.len());
+    &text[..end]
+}
+
+/// Returns the `<...>` generic parameter clause at the start of `text`, if any.
+fn generic_parameters(text: &str) -> Option<&str> {
+    if !text.starts_with('<') {
+        return None;
+    }
+    let end = balanced_end(text.as_bytes(), 0, b'<', b'>');
+    Some(&text[..end])
+}
+
+/// Returns the content of the parameter list at the start of `text`, if any.
+fn parameter_list(text: &str) -> Option<&str> {
+    let text = text.trim_start();
+    if !text.starts_with('(') {
+        return None;
+    }
+    let end = balanced_end(text.as_bytes(), 0, b'(', b')');
+    Some(&text[1..end.saturating_sub(1)])
+}
+
+fn where_clause(text: &str) -> Option<&str> {
+    find_top_level(text, &[" where "]).map(|position| text[position..].trim())
+}
+
+fn generic_signature(generics: Option<&str>, where_clause: Option<&str>) -> Option<String> {
+    match (generics, where_clause) {
+        (None, None) => None,
+        (generics, where_clause) => Some(collapse_whitespace(&format!("{} {}", generics.unwrap_or_default(), where_clause.unwrap_or_default()))),
+    }
+}
+
+/// Formats the argument labels of a function or subscript as in `name(label:_:)`.
+fn argument_labels(parameters: &str, subscript: bool) -> String {
+    split_top_level(parameters, ',')
+        .iter()
+        .filter(|parameter| !parameter.trim().is_empty())
+        .map(|parameter| {
+            let end = find_top_level(parameter, &[":"]).unwrap_or(parameter.len());
+            let names: Vec<&str> = parameter[..end].split_whitespace().collect();
+            match names.as_slice() {
+                [label, _] => format!("{}:", label),
+                // Subscript parameters have no argument label unless one is spelled out.
+                [_] if subscript => "_:".to_string(),
+                [name] => format!("{}:", name),
+                _ => "_:".to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Formats the labels of an enum case payload, where unlabeled values are `_`.
+fn case_labels(parameters: &str) -> String {
+    split_top_level(parameters, ',')
+        .iter()
+        .map(|parameter| match find_top_level(parameter, &[":"]) {
+            Some(end) => format!("{}:", parameter[..end].trim()),
+            None => "_:".to_string(),
+        })
+        .collect()
+}
+
+/// Finds the first of `needles` outside of parentheses, brackets and angle brackets.
+fn find_top_level(text: &str, needles: &[&str]) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut depth = 0i32;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                i = string_end(bytes, i);
+                continue;
+            }
+            b'(' | b'[' | b'<' => depth += 1,
+            b'>' if i > 0 && bytes[i - 1] == b'-' => {}
+            b')' | b']' | b'>' => depth -= 1,
+            _ if depth == 0 && needles.iter().any(|needle| bytes[i..].starts_with(needle.as_bytes())) => return Some(i),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+pub(crate) fn split_top_level(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut rest = text;
+    let separator = separator.to_string();
+    while let Some(position) = find_top_level(rest, &[separator.as_str()]) {
+        parts.push(&rest[..position]);
+        rest = &rest[position + 1..];
+    }
+    parts.push(rest);
+    parts
+}
+
+/// Appends the accessors of a property or subscript body (e.g. `{ get set }`) to its signature.
+fn append_accessors(declaration: &mut ApiDeclaration, body: &[u8]) {
+    if !matches!(declaration.kind, DeclarationKind::Var | DeclarationKind::Subscript) {
+        return;
+    }
+    // Only words at the top level of the body are accessors; the rest is their implementation.
+    let mut depth = 0;
+    let mut top_level = String::new();
+    for &byte in body {
+        match byte {
+            b'{' => depth += 1,
+            b'}' => depth -= 1,
+            _ if depth == 0 => top_level.push(byte as char),
+            _ => {}
+        }
+    }
+    let accessors: Vec<&str> = top_level
+        .split(|character: char| !(character.is_alphanumeric() || character == '_'))
+        .filter(|word| ACCESSORS.contains(word))
+        .collect();
+    if !accessors.is_empty() {
+        declaration.signature = format!("{} {{ {} }}", declaration.signature, accessors.join(" "));
+    }
+}
+
+/// Returns the position right after the string literal starting at `start`.
+fn string_end(bytes: &[u8], start: usize) -> usize {
+    if bytes[start..].starts_with(b"\"\"\"") {
+        return find(bytes, start + 3, b"\"\"\"").map_or(bytes.len(), |end| end + 3);
+    }
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'"' | b'\n' => return i + 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+/// Returns the position right after the `{ ... }` block starting at `start`.
+fn block_end(bytes: &[u8], start: usize) -> usize {
+    balanced_end(bytes, start, b'{', b'}')
+}
+
+/// Returns the position right after the delimiter closing the one at `start`.
+fn balanced_end(bytes: &[u8], start: usize, open: u8, close: u8) -> usize {
+    let mut depth = 0;
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                i = string_end(bytes, i);
+                continue;
+            }
+            b'/' if open == b'{' && bytes.get(i + 1) == Some(&b'/') => {
+                i = bytes[i..].iter().position(|&byte| byte == b'\n').map_or(bytes.len(), |end| i + end);
+                continue;
+            }
+            // `->` is not a closing angle bracket.
+            b'>' if close == b'>' && i > 0 && bytes[i - 1] == b'-' => {}
+            byte if byte == open => depth += 1,
+            byte if byte == close => {
+                depth -= 1;
+                if depth == 0 {
+                    return i + 1;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+fn find(bytes: &[u8], start: usize, needle: &[u8]) -> Option<usize> {
+    bytes.get(start..)?.windows(needle.len()).position(|window| window == needle).map(|position| start + position)
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+fn take(buffer: &mut Vec<u8>) -> String {
+    String::from_utf8_lossy(&std::mem::take(buffer)).into_owned()
+}
+
+fn take_string(buffer: &mut String) -> String {
+    std::mem::take(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework_info::{SwiftInterfaceInfo, SwiftModuleFlags};
+    use crate::xcframework_processing::archive_source::MemorySource;
+
+    const INTERFACE: &str = r#"// swift-interface-format-version: 1.0
+// swift-module-flags: -target arm64-apple-ios15.0 -enable-library-evolution -module-name Greeter
+import Foundation
+#if compiler(>=5.3) && $Macros
+@freestanding(expression) public macro stringify<T>(_ value: T) -> (T, Swift.String) = #externalMacro(module: "GreeterMacros", type: "StringifyMacro")
+#else
+public func stringifyFallback()
+#endif
+@frozen public enum Mood : Swift.String {
+  case happy, sad
+  case other(Swift.String, reason: Swift.Int)
+}
+@available(iOS 15.0, macOS 12.0, *)
+@objc open class Greeter : ObjectiveC.NSObject {
+  @objc public var name: Swift.String {
+    @objc get
+    @objc set
+  }
+  public var count: Swift.Int {
+    get
+  }
+  internal var hidden: Swift.Int
+  @inlinable public func greet(_ name: Swift.String, times: Swift.Int = 1) -> Swift.String {
+    return name
+  }
+  public subscript(index: Swift.Int) -> Swift.String {
+    get
+  }
+  @objc deinit
+}
+public protocol Greeting {
+  associatedtype Name = Swift.String
+  func greet() -> Self.Name
+}
+extension Greeter.Greeter : Greeter.Greeting {
+  public func greet() -> Swift.String
+}
+extension Greeter.Mood {
+  public static func random() -> Greeter.Mood
+  internal func secret()
+}
+"#;
+
+    fn named<'a>(declarations: &'a [ApiDeclaration], name: &str) -> &'a ApiDeclaration {
+        declarations
+            .iter()
+            .find(|declaration| declaration.name == name)
+            .unwrap_or_else(|| panic!("{} not found in {:?}", name, declarations))
+    }
+
+    #[test]
+    fn parses_a_representative_interface() {
+        let declarations = parse_interface(INTERFACE, "Greeter");
+        let names: Vec<&str> = declarations.iter().map(|declaration| declaration.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "stringify(_:)",
+                "Mood",
+                "Mood.happy",
+                "Mood.sad",
+                "Mood.other(_:reason:)",
+                "Greeter",
+                "Greeter.name",
+                "Greeter.count",
+                "Greeter.greet(_:times:)",
+                "Greeter.subscript(_:)",
+                "Greeting",
+                "Greeting.Name",
+                "Greeting.greet()",
+                "Greeter",
+                "Greeter.greet()",
+                "Mood.random()",
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_the_first_conditional_branch() {
+        let declarations = parse_interface(INTERFACE, "Greeter");
+        assert_eq!(named(&declarations, "stringify(_:)").kind, DeclarationKind::Macro);
+        assert!(declarations.iter().all(|declaration| declaration.name != "stringifyFallback()"));
+
+        let nested = "#if A\n#if B\npublic func a()\n#else\npublic func b()\n#endif\n#elseif C\npublic func c()\n#endif\npublic func d()\n";
+        let names: Vec<String> = parse_interface(nested, "M").into_iter().map(|declaration| declaration.name).collect();
+        assert_eq!(names, ["a()", "d()"]);
+    }
+
+    #[test]
+    fn extracts_accessors_without_attributes_or_bodies() {
+        let declarations = parse_interface(INTERFACE, "Greeter");
+        assert_eq!(named(&declarations, "Greeter.name").signature, "public var name: Swift.String { get set }");
+        assert_eq!(named(&declarations, "Greeter.count").signature, "public var count: Swift.Int { get }");
+        assert_eq!(named(&declarations, "Greeter.subscript(_:)").signature, "public subscript(index: Swift.Int) -> Swift.String { get }");
+        assert_eq!(
+            named(&declarations, "Greeter.greet(_:times:)").signature,
+            "public func greet(_ name: Swift.String, times: Swift.Int = 1) -> Swift.String"
+        );
+    }
+
+    #[test]
+    fn separates_availability_from_other_attributes() {
+        let declarations = parse_interface(INTERFACE, "Greeter");
+        let greeter = named(&declarations, "Greeter");
+        assert_eq!(greeter.availability, ["@available(iOS 15.0, macOS 12.0, *)"]);
+        assert_eq!(greeter.attributes, ["@objc"]);
+        assert_eq!(greeter.signature, "open class Greeter : ObjectiveC.NSObject");
+        assert_eq!(named(&declarations, "Mood").attributes, ["@frozen"]);
+        assert_eq!(named(&declarations, "Greeter.greet(_:times:)").attributes, ["@inlinable"]);
+        assert_eq!(named(&declarations, "stringify(_:)").attributes, ["@freestanding(expression)"]);
+
+        // Attributes on their own line apply to the next declaration.
+        let declarations = parse_interface("@available(*, unavailable)\n@discardableResult\npublic func old() -> Swift.Int\n", "M");
+        assert_eq!(declarations[0].availability, ["@available(*, unavailable)"]);
+        assert_eq!(declarations[0].attributes, ["@discardableResult"]);
+    }
+
+    #[test]
+    fn names_extension_members_after_the_extended_type() {
+        let declarations = parse_interface(INTERFACE, "Greeter");
+        // Extensions are only reported when they add conformances.
+        let extensions: Vec<&ApiDeclaration> = declarations.iter().filter(|declaration| declaration.kind == DeclarationKind::Extension).collect();
+        assert_eq!(extensions.len(), 1);
+        assert_eq!(extensions[0].name, "Greeter");
+        assert_eq!(extensions[0].signature, "extension Greeter.Greeter : Greeter.Greeting");
+
+        let random = named(&declarations, "Mood.random()");
+        assert_eq!(random.container, Some(DeclarationKind::Extension));
+        assert!(declarations.iter().all(|declaration| !declaration.name.contains("secret") && !declaration.name.contains("hidden")));
+    }
+
+    #[test]
+    fn merges_declarations_of_every_slice() {
+        let library = |identifier: &str| LibraryInfo {
+            library_identifier: identifier.to_string(),
+            library_path: "Greeter.framework".to_string(),
+            swift_interfaces: vec![SwiftInterfaceInfo {
+                path: "Greeter.framework/Modules/Greeter.swiftmodule/arm64.swiftinterface".to_string(),
+                architecture: "arm64".to_string(),
+                compiler_info: None,
+                compiler_version: None,
+                flags: SwiftModuleFlags { module_name: Some("Greeter".to_string()), ..Default::default() },
+            }],
+            ..Default::default()
+        };
+        let interface = "Greeter.framework/Modules/Greeter.swiftmodule/arm64.swiftinterface";
+        let mut archive = MemorySource::new([
+            ("Greeter.xcframework/Info.plist".to_string(), String::new()),
+            (format!("Greeter.xcframework/ios-arm64/{}", interface), "public func shared()\npublic func device()\n".to_string()),
+            (format!("Greeter.xcframework/ios-arm64-simulator/{}", interface), "public func shared()\n".to_string()),
+        ]);
+        let index = ArchiveIndex::build(archive.entries().unwrap());
+        let declarations = extract_api(&mut archive, &index, &[library("ios-arm64"), library("ios-arm64-simulator")]);
+
+        assert_eq!(declarations.len(), 2);
+        assert!(named(&declarations, "shared()").library_identifiers.is_empty());
+        assert_eq!(named(&declarations, "device()").library_identifiers, ["ios-arm64"]);
+    }
+
+    #[test]
+    fn parses_unicode_identifiers_and_operators() {
+        let declarations = parse_interface(
+            "public let π: Swift.Double\npublic func ∑(values: [Swift.Int]) -> Swift.Int\npublic static func ⊕ (lhs: M.V, rhs: M.V) -> M.V\n",
+            "M",
+        );
+        let signatures: Vec<&str> = declarations.iter().map(|declaration| declaration.signature.as_str()).collect();
+        assert_eq!(declarations.len(), 3, "{:?}", signatures);
+        assert!(signatures.iter().any(|signature| signature.contains("π")));
+        assert!(signatures.iter().any(|signature| signature.contains("∑")));
+        assert!(signatures.iter().any(|signature| signature.contains("⊕")));
+    }
+}
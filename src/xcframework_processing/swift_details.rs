@@ -1,36 +1,247 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use zip::ZipArchive;
-use crate::framework_info::FrameworkInfo;
-
-pub fn extract_swift_details(archive: &mut ZipArchive<File>, framework_info: &mut FrameworkInfo) {
-    for i in 0..archive.len() {
-        if let Ok(mut file) = archive.by_index(i) {
-            if file.name().ends_with(".swiftinterface") {
-                let reader = BufReader::new(&mut file);
-                for line in reader.lines().filter_map(Result::ok) {
-                    if line.starts_with("// swift-compiler-version: ") {
-                        let compiler_info = line.trim_start_matches("// swift-compiler-version: ").to_string();
-                        framework_info.swift_compiler_info = Some(compiler_info.clone());
-
-                        if let Some(swiftlang_version) = compiler_info.split_whitespace().find(|&part| part.contains("swiftlang-")) {
-                            let version = swiftlang_version.split('-').nth(1).unwrap_or("").to_string();
-                            framework_info.swift_compiler_version = Some(version);
-                        }
-                    } else if line.starts_with("// swift-module-flags: ") {
-                        let flags = line.trim_start_matches("// swift-module-flags: ").split_whitespace().collect::<Vec<&str>>();
-                        for (i, flag) in flags.iter().enumerate() {
-                            match *flag {
-                                "-module-name" if i + 1 < flags.len() => framework_info.framework_name = Some(flags[i + 1].to_string()),
-                                "-swift-version" if i + 1 < flags.len() => framework_info.swift_version = Some(flags[i + 1].to_string()),
-                                "-enable-library-evolution" => framework_info.library_evolution_enabled = true,
-                                _ => {}
-                            }
-                        }
-                    }
+use std::fmt::Display;
+use crate::framework_info::{FrameworkInfo, OptimizationLevel, SwiftInterfaceInfo, SwiftModuleFlags, Warning};
+use crate::xcframework_processing::archive_index::{ArchiveIndex, FileKind};
+use crate::xcframework_processing::archive_source::ArchiveSource;
+
+/// Reads the public `.swiftinterface` of every architecture of a slice.
+pub fn extract_swift_interfaces(
+    archive: &mut dyn ArchiveSource,
+    index: &ArchiveIndex,
+    library_identifier: &str,
+    library_path: &str,
+) -> Vec<SwiftInterfaceInfo> {
+    let modules_path = format!("{}/Modules", library_path);
+    let slice_prefix = index.slice_path(library_identifier, "");
+    let names: Vec<String> = index
+        .library_files(library_identifier, &modules_path)
+        .filter(|file| file.kind == FileKind::SwiftInterface)
+        // Private and package interfaces repeat the flags of the public one.
+        .filter(|file| !file.name.ends_with(".private.swiftinterface") && !file.name.ends_with(".package.swiftinterface"))
+        .map(|file| file.name.clone())
+        .collect();
+
+    let mut interfaces = Vec::with_capacity(names.len());
+    for name in names {
+        let Ok(contents) = archive.read_to_string(&name) else {
+            continue;
+        };
+        let path = name.strip_prefix(&slice_prefix).unwrap_or(&name);
+        interfaces.push(parse_swift_interface(path, &contents));
+    }
+    interfaces
+}
+
+/// Parses the header comments of a `.swiftinterface` file.
+pub fn parse_swift_interface(path: &str, contents: &str) -> SwiftInterfaceInfo {
+    // Interfaces are named after their target triple (e.g. `arm64-apple-ios15.0-simulator.swiftinterface`),
+    // which the `-target` flag repeats.
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    let mut interface = SwiftInterfaceInfo {
+        path: path.to_string(),
+        architecture: file_name.split(['-', '.']).next().unwrap_or_default().to_string(),
+        compiler_info: None,
+        compiler_version: None,
+        flags: SwiftModuleFlags::default(),
+    };
+
+    for line in contents.lines() {
+        if line.starts_with("// swift-compiler-version: ") {
+            let compiler_info = line.trim_start_matches("// swift-compiler-version: ").to_string();
+
+            if let Some(swiftlang_version) = compiler_info.split_whitespace().find(|&part| part.contains("swiftlang-")) {
+                let version = swiftlang_version.split('-').nth(1).unwrap_or("").to_string();
+                interface.compiler_version = Some(version);
+            }
+            interface.compiler_info = Some(compiler_info);
+        } else if let Some(flags) = line
+            .strip_prefix("// swift-module-flags: ")
+            .or_else(|| line.strip_prefix("// swift-module-flags-ignorable: "))
+        {
+            parse_module_flags(flags, &mut interface.flags);
+        } else if !line.starts_with("//") && !line.trim().is_empty() {
+            // The header is over once declarations start.
+            break;
+        }
+    }
+    interface
+}
+
+/// Parses a `swift-module-flags` line into `flags`. Both `-flag value` and
+/// `-flag=value` spellings are accepted.
+pub fn parse_module_flags(line: &str, flags: &mut SwiftModuleFlags) {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i];
+        i += 1;
+        let (name, inline_value) = match token.split_once('=') {
+            Some((name, value)) if token.starts_with('-') => (name, Some(value)),
+            _ => (token, None),
+        };
+        let takes_value = matches!(
+            name,
+            "-target"
+                | "-target-variant"
+                | "-module-name"
+                | "-module-abi-name"
+                | "-package-name"
+                | "-user-module-version"
+                | "-swift-version"
+                | "-strict-concurrency"
+                | "-enforce-exclusivity"
+                | "-enable-upcoming-feature"
+                | "-enable-experimental-feature"
+        );
+        let value = match inline_value {
+            Some(value) => Some(value.to_string()),
+            None if takes_value && i < tokens.len() => {
+                i += 1;
+                Some(tokens[i - 1].to_string())
+            }
+            None => None,
+        };
+
+        match (name, value) {
+            ("-target", value) => flags.target = value,
+            ("-target-variant", value) => flags.target_variant = value,
+            ("-module-name", value) => flags.module_name = value,
+            ("-module-abi-name", value) => flags.module_abi_name = value,
+            ("-package-name", value) => flags.package_name = value,
+            ("-user-module-version", value) => flags.user_module_version = value,
+            ("-swift-version", value) => flags.swift_version = value,
+            ("-strict-concurrency", value) => flags.strict_concurrency = value,
+            ("-enforce-exclusivity", value) => flags.enforce_exclusivity = value,
+            ("-enable-upcoming-feature", Some(feature)) => push_unique(&mut flags.upcoming_features, feature),
+            ("-enable-experimental-feature", Some(feature)) => push_unique(&mut flags.experimental_features, feature),
+            ("-Onone", _) => flags.optimization = Some(OptimizationLevel::None),
+            ("-O", _) => flags.optimization = Some(OptimizationLevel::Speed),
+            ("-Osize", _) => flags.optimization = Some(OptimizationLevel::Size),
+            ("-Ounchecked", _) => flags.optimization = Some(OptimizationLevel::Unchecked),
+            ("-enable-library-evolution", _) => flags.library_evolution = true,
+            ("-enable-testing", _) => flags.testing = true,
+            ("-enable-bare-slash-regex", _) => flags.bare_slash_regex = true,
+            _ => {
+                // Unknown flags keep the value that follows them, if any; `-Xcc` and friends always have one.
+                let mut flag = token.to_string();
+                let forwards = matches!(token, "-Xcc" | "-Xllvm" | "-Xfrontend" | "-Xclang-linker");
+                if token.starts_with('-')
+                    && inline_value.is_none()
+                    && tokens.get(i).is_some_and(|next| forwards || !next.starts_with('-'))
+                {
+                    flag = format!("{} {}", flag, tokens[i]);
+                    i += 1;
                 }
-                break;
+                push_unique(&mut flags.other, flag);
             }
         }
     }
 }
+
+fn push_unique(list: &mut Vec<String>, value: String) {
+    if !list.contains(&value) {
+        list.push(value);
+    }
+}
+
+/// Warns about interfaces emitted by a debug (`-Onone`) or testable (`-enable-testing`)
+/// build: such binaries are slower and expose internal symbols, and should not be distributed.
+pub fn check_distribution_flags(interfaces: &[SwiftInterfaceInfo], library_path: &str, warnings: &mut Vec<Warning>) {
+    let architectures = |predicate: fn(&SwiftModuleFlags) -> bool| -> Vec<&str> {
+        interfaces
+            .iter()
+            .filter(|interface| predicate(&interface.flags))
+            .map(|interface| interface.architecture.as_str())
+            .collect()
+    };
+
+    let debug = architectures(|flags| flags.optimization == Some(OptimizationLevel::None));
+    if !debug.is_empty() {
+        warnings.push(Warning {
+            code: "debug-build".to_string(),
+            message: format!("Swift module was built without optimizations (-Onone) for {}", debug.join(", ")),
+            path: Some(library_path.to_string()),
+        });
+    }
+    let testable = architectures(|flags| flags.testing);
+    if !testable.is_empty() {
+        warnings.push(Warning {
+            code: "testable-build".to_string(),
+            message: format!("Swift module was built with -enable-testing for {}", testable.join(", ")),
+            path: Some(library_path.to_string()),
+        });
+    }
+}
+
+/// Reports the Swift details of the framework from the interfaces of its slices,
+/// keeping only the values every interface agrees on and warning otherwise.
+pub fn summarize_swift_details(framework_info: &mut FrameworkInfo) {
+    let Some(libraries) = &framework_info.available_libraries else {
+        return;
+    };
+    let interfaces: Vec<(&str, &SwiftInterfaceInfo)> = libraries
+        .iter()
+        .flat_map(|library| library.swift_interfaces.iter().map(move |interface| (library.library_identifier.as_str(), interface)))
+        .collect();
+    if interfaces.is_empty() {
+        return;
+    }
+
+    let warnings = &mut framework_info.warnings;
+    let compiler_info = consistent(&interfaces, "Swift compiler", |interface| interface.compiler_info.clone(), Some(&mut *warnings));
+    // The compiler version is part of the compiler info, already compared above.
+    let compiler_version = consistent(&interfaces, "Swift compiler version", |interface| interface.compiler_version.clone(), None);
+    let swift_version = consistent(&interfaces, "Swift language version", |interface| interface.flags.swift_version.clone(), Some(&mut *warnings));
+    let module_name = consistent(&interfaces, "module name", |interface| interface.flags.module_name.clone(), Some(&mut *warnings));
+    let library_evolution = consistent(
+        &interfaces,
+        "library evolution",
+        |interface| Some(interface.flags.library_evolution),
+        Some(&mut *warnings),
+    );
+
+    framework_info.swift_compiler_info = compiler_info;
+    framework_info.swift_compiler_version = compiler_version;
+    framework_info.swift_version = swift_version;
+    if module_name.is_some() {
+        framework_info.framework_name = module_name;
+    }
+    framework_info.library_evolution_enabled = library_evolution.unwrap_or(false);
+}
+
+/// Returns the value shared by every interface, or `None` (with a warning, if
+/// `warnings` is given) listing which slices and architectures disagree.
+fn consistent<T: PartialEq + Display>(
+    interfaces: &[(&str, &SwiftInterfaceInfo)],
+    what: &str,
+    value: impl Fn(&SwiftInterfaceInfo) -> Option<T>,
+    warnings: Option<&mut Vec<Warning>>,
+) -> Option<T> {
+    let mut groups: Vec<(Option<T>, Vec<String>)> = Vec::new();
+    for (library_identifier, interface) in interfaces {
+        let value = value(interface);
+        let location = format!("{}/{}", library_identifier, interface.architecture);
+        match groups.iter_mut().find(|(existing, _)| *existing == value) {
+            Some((_, locations)) => locations.push(location),
+            None => groups.push((value, vec![location])),
+        }
+    }
+
+    if groups.len() == 1 {
+        return groups.pop().and_then(|(value, _)| value);
+    }
+    if let Some(warnings) = warnings {
+        let values: Vec<String> = groups
+            .iter()
+            .map(|(value, locations)| {
+                let value = value.as_ref().map_or("none".to_string(), |value| format!("'{}'", value));
+                format!("{} ({})", value, locations.join(", "))
+            })
+            .collect();
+        warnings.push(Warning {
+            code: "swift-details-mismatch".to_string(),
+            message: format!("Slices disagree on the {}: {}", what, values.join("; ")),
+            path: None,
+        });
+    }
+    None
+}
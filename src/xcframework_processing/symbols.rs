@@ -0,0 +1,213 @@
+use std::collections::HashSet;
+use std::fmt;
+use goblin::mach::exports::{ExportInfo, EXPORT_SYMBOL_FLAGS_WEAK_DEFINITION};
+use goblin::mach::symbols::{Nlist, N_PEXT, N_STAB, N_TYPE, N_UNDF, N_WEAK_DEF, N_WEAK_REF};
+use symbolic_common::{Language, Name, NameMangling};
+use symbolic_demangle::{Demangle, DemangleOptions};
+use crate::framework_info::{SymbolInfo, SymbolKind, SymbolTable};
+use crate::xcframework_processing::macho::{ArchitectureImage, MachOFile};
+
+/// Library ordinals of undefined symbols that do not point at a linked library.
+const SELF_LIBRARY_ORDINAL: usize = 0;
+const DYNAMIC_LOOKUP_ORDINAL: usize = 0xfe;
+const EXECUTABLE_ORDINAL: usize = 0xff;
+
+/// Selects the symbols reported by [`crate::xcframework_processing::collect_symbols`].
+#[derive(Debug, Clone, Default)]
+pub struct SymbolFilter {
+    pub library_identifier: Option<String>,
+    pub architecture: Option<String>,
+    pub kind: Option<SymbolKind>,
+    /// Only weak definitions and weak references.
+    pub weak: bool,
+    /// Text the mangled or demangled name must contain.
+    pub name: Option<String>,
+}
+
+impl SymbolFilter {
+    pub fn matches(&self, symbol: &SymbolInfo) -> bool {
+        self.kind.is_none_or(|kind| symbol.kind == kind)
+            && (!self.weak || symbol.weak)
+            && self.name.as_deref().is_none_or(|name| {
+                symbol.name.contains(name) || symbol.demangled.as_deref().is_some_and(|demangled| demangled.contains(name))
+            })
+    }
+}
+
+/// Lists the symbols of an architecture, without demangling them (see [`demangle_symbols`]).
+/// Dynamic libraries export what their export trie (`LC_DYLD_INFO` or `LC_DYLD_EXPORTS_TRIE`)
+/// lists; static archives what their members define, and only reference what no member defines.
+pub fn image_symbols(image: &ArchitectureImage) -> Vec<SymbolInfo> {
+    let mut symbols = if image.is_archive {
+        archive_symbols(&image.objects)
+    } else {
+        image.objects.first().map(dylib_symbols).unwrap_or_default()
+    };
+    symbols.sort_by(|a, b| (a.kind, &a.name).cmp(&(b.kind, &b.name)));
+    symbols
+}
+
+fn dylib_symbols(file: &MachOFile) -> Vec<SymbolInfo> {
+    let mut symbols = Vec::new();
+    let exports = file.macho.exports().unwrap_or_default();
+    for export in &exports {
+        let (flags, library) = match export.info {
+            ExportInfo::Regular { flags, .. } | ExportInfo::Stub { flags, .. } => (flags, None),
+            ExportInfo::Reexport { lib, flags, .. } => (flags, Some(lib.to_string())),
+        };
+        symbols.push(symbol(
+            &export.name,
+            SymbolKind::Exported,
+            flags & EXPORT_SYMBOL_FLAGS_WEAK_DEFINITION != 0,
+            library,
+            None,
+        ));
+    }
+
+    for (name, nlist) in file.macho.symbols().flatten() {
+        if nlist.n_type & N_STAB != 0 || !nlist.is_global() {
+            continue;
+        }
+        if is_undefined(&nlist) {
+            // Two-level namespace: the high byte of `n_desc` is the ordinal of the library.
+            let ordinal = (nlist.n_desc >> 8) as usize;
+            let library = match ordinal {
+                SELF_LIBRARY_ORDINAL | DYNAMIC_LOOKUP_ORDINAL | EXECUTABLE_ORDINAL => None,
+                _ => file.macho.libs.get(ordinal).map(|library| library.to_string()),
+            };
+            symbols.push(symbol(name, SymbolKind::Undefined, nlist.n_desc & N_WEAK_REF != 0, library, None));
+        } else if exports.is_empty() && nlist.n_type & N_PEXT == 0 {
+            // Binaries without an export trie export their external symbols.
+            symbols.push(symbol(name, SymbolKind::Exported, nlist.n_desc & N_WEAK_DEF != 0, None, None));
+        }
+    }
+    symbols
+}
+
+fn archive_symbols(objects: &[MachOFile]) -> Vec<SymbolInfo> {
+    let mut exported = Vec::new();
+    let mut undefined = Vec::new();
+    for object in objects {
+        for (name, nlist) in object.macho.symbols().flatten() {
+            // Private externs (`N_PEXT`) are still visible to the app the archive is linked into.
+            if nlist.n_type & N_STAB != 0 || !(nlist.is_global() || nlist.n_type & N_PEXT != 0) {
+                continue;
+            }
+            let object_name = Some(object.name.clone());
+            if is_undefined(&nlist) {
+                undefined.push(symbol(name, SymbolKind::Undefined, nlist.n_desc & N_WEAK_REF != 0, None, object_name));
+            } else {
+                exported.push(symbol(name, SymbolKind::Exported, nlist.n_desc & N_WEAK_DEF != 0, None, object_name));
+            }
+        }
+    }
+
+    let defined: HashSet<String> = exported.iter().map(|symbol| symbol.name.clone()).collect();
+    let mut referenced = HashSet::new();
+    undefined.retain(|symbol| !defined.contains(&symbol.name) && referenced.insert(symbol.name.clone()));
+    exported.extend(undefined);
+    exported
+}
+
+/// Undefined symbols have no section; common symbols (`N_UNDF` with a size) are definitions.
+fn is_undefined(nlist: &Nlist) -> bool {
+    nlist.n_type & N_TYPE == N_UNDF && nlist.n_value == 0
+}
+
+fn symbol(name: &str, kind: SymbolKind, weak: bool, library: Option<String>, object: Option<String>) -> SymbolInfo {
+    SymbolInfo { name: name.to_string(), demangled: None, kind, weak, library, object }
+}
+
+/// Fills the demangled name of Swift and C++ symbols.
+pub fn demangle_symbols(symbols: &mut [SymbolInfo]) {
+    for symbol in symbols {
+        symbol.demangled = demangle(&symbol.name);
+    }
+}
+
+/// Lists the Objective-C selectors a Mach-O file sends, from the pointers in `__objc_selrefs`
+/// into the `__objc_methname` strings. Chained-fixup rebases keep the target in their low 36 bits,
+/// either as an address or as an offset from the start of `__TEXT`.
+pub fn selector_references(file: &MachOFile) -> Vec<String> {
+    let mut text_address = 0;
+    let mut method_names = Vec::new();
+    let mut references = Vec::new();
+    for segment in &file.macho.segments {
+        if matches!(segment.name(), Ok("__TEXT")) {
+            text_address = segment.vmaddr;
+        }
+        for (section, data) in segment.sections().unwrap_or_default() {
+            match section.name() {
+                Ok("__objc_methname") => method_names.push((section.addr, data)),
+                Ok("__objc_selrefs") => references.push(data),
+                _ => {}
+            }
+        }
+    }
+
+    let pointer_size = if file.macho.is_64 { 8 } else { 4 };
+    let mut seen = HashSet::new();
+    let mut selectors = Vec::new();
+    for data in references {
+        for chunk in data.chunks_exact(pointer_size) {
+            let value = if pointer_size == 8 {
+                u64::from_le_bytes(chunk.try_into().unwrap_or_default())
+            } else {
+                u32::from_le_bytes(chunk.try_into().unwrap_or_default()) as u64
+            };
+            let target = value & 0xf_ffff_ffff;
+            let name = [value, target, text_address + target]
+                .into_iter()
+                .find_map(|address| method_name(&method_names, address));
+            if let Some(name) = name {
+                if seen.insert(name.clone()) {
+                    selectors.push(name);
+                }
+            }
+        }
+    }
+    selectors
+}
+
+fn method_name(method_names: &[(u64, &[u8])], address: u64) -> Option<String> {
+    method_names.iter().find_map(|(start, data)| {
+        let offset = address.checked_sub(*start)? as usize;
+        let bytes = data.get(offset..)?;
+        let end = bytes.iter().position(|&byte| byte == 0)?;
+        std::str::from_utf8(&bytes[..end]).ok().map(str::to_string)
+    })
+}
+
+/// Demangles a Swift or C++ symbol name, without the leading underscore of C symbols.
+pub fn demangle(name: &str) -> Option<String> {
+    let stripped = name.strip_prefix('_').unwrap_or(name);
+    let name = Name::new(stripped, NameMangling::Mangled, Language::Unknown);
+    if !matches!(name.detect_language(), Language::Swift | Language::Cpp) {
+        return None;
+    }
+    name.demangle(DemangleOptions::complete()).filter(|demangled| demangled != stripped)
+}
+
+impl fmt::Display for SymbolTable {
+    /// One line per symbol, as `kind name demangled-name (library)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} ({}):", self.library_identifier, self.architecture)?;
+        for symbol in &self.symbols {
+            let kind = match (symbol.kind, symbol.weak) {
+                (SymbolKind::Exported, false) => "exported",
+                (SymbolKind::Exported, true) => "exported (weak)",
+                (SymbolKind::Undefined, false) => "undefined",
+                (SymbolKind::Undefined, true) => "undefined (weak)",
+            };
+            write!(f, "  {:<16} {}", kind, symbol.name)?;
+            if let Some(demangled) = &symbol.demangled {
+                write!(f, "  {}", demangled)?;
+            }
+            if let Some(library) = symbol.library.as_ref().or(symbol.object.as_ref()) {
+                write!(f, "  ({})", library)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}